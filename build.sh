@@ -7,3 +7,4 @@ cross build --target arm-unknown-linux-gnueabi
 
 scp target/arm-unknown-linux-gnueabi/debug/system_controller drake@192.168.50.106:~
 scp src/config.toml drake@192.168.50.106:~
+scp -r src/missions drake@192.168.50.106:~
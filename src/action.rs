@@ -0,0 +1,157 @@
+use std::{
+    collections::VecDeque,
+    sync::{ Arc, Mutex },
+};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use crate::{
+    command::{
+        self,
+        BallastPositionCommand,
+        Command,
+        CommandSource,
+        ServoCommand,
+        SystemCommand,
+    },
+    definitions::DirectionVector,
+    hardware_model::{ Light, Propulsion, Submarine },
+    traits::SubmarineComponent,
+};
+use common::commands::{ BallastCommand, LightCommand };
+
+/*
+ * Something the controller can be configured to do in response to an
+ * event, such as an input changing state. Components are named as in the
+ * hardware config.
+ */
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    Log { message: String },
+    EnableComponent { component: String },
+    DisableComponent { component: String },
+    Ballast { state: BallastAction },
+    SetBallastPosition { component: String, position: u32 },
+    SetServo { component: String, angle_deg: f32 },
+    StopThrusters,
+    Light { state: LightAction },
+    Thrust { x: f32, y: f32 },
+    EmergencyStop,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BallastAction {
+    Idle,
+    Intake,
+    Discharge,
+}
+
+impl BallastAction {
+    pub fn to_command(self) -> Command {
+        Command::Ballast(Arc::new(match self {
+            BallastAction::Idle => BallastCommand::Idle,
+            BallastAction::Intake => BallastCommand::Intake,
+            BallastAction::Discharge => BallastCommand::Discharge,
+        }))
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LightAction {
+    Off,
+    On,
+    Blink,
+    Beacon,
+}
+
+static PENDING_ACTIONS: Lazy<Mutex<VecDeque<Action>>> =
+    Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/*
+ * Queues an action to run at the start of the next tick. Components call
+ * this since they can't reach the rest of the submarine.
+ */
+pub fn trigger(action: &Action) {
+    PENDING_ACTIONS.lock().unwrap().push_back(action.clone());
+}
+
+pub fn run_pending(sub: &mut Submarine) {
+    let pending: Vec<Action> = PENDING_ACTIONS.lock().unwrap().drain(..).collect();
+
+    for action in pending.iter() {
+        run(action, sub);
+    }
+}
+
+/*
+ * Actions that act on components are run as the equivalent command, so
+ * they get the same checks as commands from the operator.
+ */
+pub fn run(action: &Action, sub: &mut Submarine) {
+    run_as(action, sub, CommandSource::Controller);
+}
+
+pub fn run_as(action: &Action, sub: &mut Submarine, source: CommandSource) {
+    let command = match action {
+        Action::Log { message } => {
+            println!("{}", message);
+            return;
+        },
+        Action::EnableComponent { component } => component_id(sub, component)
+            .map(|id| Command::System(SystemCommand::EnableModule(id))),
+        Action::DisableComponent { component } => component_id(sub, component)
+            .map(|id| Command::System(SystemCommand::DisableModule(id))),
+        Action::Ballast { state } => Some(state.to_command()),
+        Action::SetBallastPosition { component, position } => component_id(sub, component)
+            .map(|id| Command::BallastPosition(BallastPositionCommand::SetPosition {
+                component: id,
+                position: *position,
+            })),
+        Action::SetServo { component, angle_deg } => component_id(sub, component)
+            .map(|id| Command::Servo(ServoCommand {
+                component: id,
+                angle_deg: *angle_deg,
+            })),
+        // stopping is always allowed, so it doesn't go through the mode
+        Action::StopThrusters => {
+            for p in sub.components_mut().iter_mut_of::<Propulsion>() {
+                p.zero_thrust();
+            }
+            return;
+        },
+        Action::Light { state } => Some(Command::Light(Arc::new(match state {
+            LightAction::Off => LightCommand::Off,
+            LightAction::On => LightCommand::On,
+            LightAction::Blink => LightCommand::Blink,
+            // the beacon isn't part of the common light commands
+            LightAction::Beacon => {
+                for l in sub.components_mut().iter_mut_of::<Light>() {
+                    if l.is_enabled() { l.beacon(); }
+                }
+                return;
+            },
+        }))),
+        Action::Thrust { x, y } => Some(Command::Thrust(DirectionVector{x: *x, y: *y})),
+        // like the operator's, this can't wait on the mode
+        Action::EmergencyStop => {
+            sub.emergency_stop(source);
+            return;
+        },
+    };
+
+    if let Some(c) = command {
+        command::dispatch_command(sub, &c, source);
+    }
+}
+
+fn component_id(sub: &Submarine, name: &str) -> Option<u8> {
+    let id = sub.components().find(name).map(|s| s.id);
+
+    if id.is_none() {
+        eprintln!("Cannot run action on {}: no such component.", name);
+    }
+
+    id
+}
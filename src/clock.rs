@@ -0,0 +1,37 @@
+use std::{
+    sync::Mutex,
+    time::{ Duration, Instant },
+};
+use once_cell::sync::Lazy;
+
+/*
+ * The controller's wall-clock time. The Pi has no real time clock, so the
+ * system clock can't be trusted without a network; instead the time is
+ * set from an external reference, such as a GPS fix, and advanced with
+ * the monotonic clock in between.
+ */
+static WALL_CLOCK: Lazy<Mutex<Option<(Duration, Instant)>>> =
+    Lazy::new(|| Mutex::new(None));
+
+pub fn set_wall_clock(since_epoch: Duration) {
+    *WALL_CLOCK.lock().unwrap() = Some((since_epoch, Instant::now()));
+}
+
+/*
+ * Time since the unix epoch, or None until the clock has been set.
+ */
+pub fn wall_clock() -> Option<Duration> {
+    WALL_CLOCK.lock().unwrap()
+        .map(|(since_epoch, set_at)| since_epoch + set_at.elapsed())
+}
+
+/*
+ * Sets the system clock as well, so logs and file times are right. Needs
+ * CAP_SYS_TIME.
+ */
+pub fn set_system_clock(since_epoch: Duration) -> Result<(), String> {
+    use nix::{ sys::time::TimeSpec, time::{ clock_settime, ClockId } };
+
+    clock_settime(ClockId::CLOCK_REALTIME, TimeSpec::from_duration(since_epoch))
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,108 @@
+/*
+ * Commands for the closed loop controllers. Like system commands, these
+ * are not part of the common command set and use their own module ID.
+ *
+ * Payload layout: [opcode, args..]
+ */
+use crate::control::pid::{ Gain, Limit };
+
+pub const AUTOPILOT_MODULE_ID: u8 = 0xC;
+
+const ENGAGE_DEPTH_HOLD_OPCODE: u8 = 0x0;
+const DISENGAGE_DEPTH_HOLD_OPCODE: u8 = 0x1;
+const SET_DEPTH_GAIN_OPCODE: u8 = 0x2;
+const ENGAGE_HEADING_HOLD_OPCODE: u8 = 0x3;
+const DISENGAGE_HEADING_HOLD_OPCODE: u8 = 0x4;
+const SET_HEADING_GAIN_OPCODE: u8 = 0x5;
+const START_AUTO_TRIM_OPCODE: u8 = 0x6;
+const ABORT_AUTO_TRIM_OPCODE: u8 = 0x7;
+const APPLY_TRIM_OPCODE: u8 = 0x8;
+const SET_DEPTH_LIMIT_OPCODE: u8 = 0x9;
+const SET_DEPTH_DEADBAND_OPCODE: u8 = 0xA;
+const SET_HEADING_LIMIT_OPCODE: u8 = 0xB;
+
+#[derive(Debug, Clone, Copy)]
+pub enum AutopilotCommand {
+    // target depth in meters
+    EngageDepthHold(f32),
+    DisengageDepthHold,
+    SetDepthGain(Gain, f32),
+    SetDepthLimit(Limit, f32),
+    SetDepthDeadband(f32),
+    // target heading in degrees clockwise from north
+    EngageHeadingHold(f32),
+    DisengageHeadingHold,
+    SetHeadingGain(Gain, f32),
+    SetHeadingLimit(Limit, f32),
+    StartAutoTrim,
+    AbortAutoTrim,
+    // moves the ballast to the stored trim position
+    ApplyTrim,
+}
+
+impl AutopilotCommand {
+    pub fn engages(&self) -> bool {
+        matches!(
+            self,
+            AutopilotCommand::EngageDepthHold(_)
+                | AutopilotCommand::EngageHeadingHold(_)
+                | AutopilotCommand::StartAutoTrim
+                | AutopilotCommand::ApplyTrim
+        )
+    }
+
+    pub fn deserialize(payload: &[u8]) -> Result<Self, ()> {
+        let opcode = *payload.get(0).ok_or(())?;
+
+        match opcode {
+            ENGAGE_DEPTH_HOLD_OPCODE =>
+                Ok(AutopilotCommand::EngageDepthHold(f32_arg(payload, 1)?)),
+            DISENGAGE_DEPTH_HOLD_OPCODE => Ok(AutopilotCommand::DisengageDepthHold),
+            SET_DEPTH_GAIN_OPCODE =>
+                Ok(AutopilotCommand::SetDepthGain(gain_arg(payload, 1)?, f32_arg(payload, 2)?)),
+            ENGAGE_HEADING_HOLD_OPCODE =>
+                Ok(AutopilotCommand::EngageHeadingHold(f32_arg(payload, 1)?)),
+            DISENGAGE_HEADING_HOLD_OPCODE => Ok(AutopilotCommand::DisengageHeadingHold),
+            SET_HEADING_GAIN_OPCODE =>
+                Ok(AutopilotCommand::SetHeadingGain(gain_arg(payload, 1)?, f32_arg(payload, 2)?)),
+            START_AUTO_TRIM_OPCODE => Ok(AutopilotCommand::StartAutoTrim),
+            ABORT_AUTO_TRIM_OPCODE => Ok(AutopilotCommand::AbortAutoTrim),
+            APPLY_TRIM_OPCODE => Ok(AutopilotCommand::ApplyTrim),
+            SET_DEPTH_LIMIT_OPCODE =>
+                Ok(AutopilotCommand::SetDepthLimit(limit_arg(payload, 1)?, f32_arg(payload, 2)?)),
+            SET_DEPTH_DEADBAND_OPCODE =>
+                Ok(AutopilotCommand::SetDepthDeadband(f32_arg(payload, 1)?)),
+            SET_HEADING_LIMIT_OPCODE =>
+                Ok(AutopilotCommand::SetHeadingLimit(limit_arg(payload, 1)?, f32_arg(payload, 2)?)),
+            _ => Err(()),
+        }
+    }
+}
+
+// 0 is proportional, 1 integral and 2 derivative
+fn gain_arg(payload: &[u8], idx: usize) -> Result<Gain, ()> {
+    match payload.get(idx).ok_or(())? {
+        0 => Ok(Gain::Proportional),
+        1 => Ok(Gain::Integral),
+        2 => Ok(Gain::Derivative),
+        _ => Err(()),
+    }
+}
+
+// 0 is the integral limit, 1 the output limit
+fn limit_arg(payload: &[u8], idx: usize) -> Result<Limit, ()> {
+    match payload.get(idx).ok_or(())? {
+        0 => Ok(Limit::Integral),
+        1 => Ok(Limit::Output),
+        _ => Err(()),
+    }
+}
+
+// NaN or infinity would poison the controllers for good
+fn f32_arg(payload: &[u8], idx: usize) -> Result<f32, ()> {
+    let bytes: [u8; 4] = payload.get(idx..idx + 4).ok_or(())?
+        .try_into().map_err(|_| ())?;
+    let value = f32::from_le_bytes(bytes);
+
+    if value.is_finite() { Ok(value) } else { Err(()) }
+}
@@ -0,0 +1,46 @@
+/*
+ * Ballast commands for drivers with position feedback. Like system
+ * commands, these are not part of the common command set and use their
+ * own module ID.
+ *
+ * Payload layout: [opcode, component ID, args..]
+ */
+pub const BALLAST_POSITION_MODULE_ID: u8 = 0xD;
+
+const SET_POSITION_OPCODE: u8 = 0x0;
+const HOME_OPCODE: u8 = 0x1;
+
+#[derive(Debug, Clone, Copy)]
+pub enum BallastPositionCommand {
+    // position in driver steps from the empty end
+    SetPosition { component: u8, position: u32 },
+    Home { component: u8 },
+}
+
+impl BallastPositionCommand {
+    pub fn deserialize(payload: &[u8]) -> Result<Self, ()> {
+        let opcode = *payload.get(0).ok_or(())?;
+        let component = *payload.get(1).ok_or(())?;
+
+        match opcode {
+            SET_POSITION_OPCODE => {
+                let position: [u8; 4] = payload.get(2..6).ok_or(())?
+                    .try_into().map_err(|_| ())?;
+
+                Ok(BallastPositionCommand::SetPosition {
+                    component,
+                    position: u32::from_le_bytes(position),
+                })
+            },
+            HOME_OPCODE => Ok(BallastPositionCommand::Home { component }),
+            _ => Err(()),
+        }
+    }
+
+    pub fn component(&self) -> u8 {
+        match self {
+            BallastPositionCommand::SetPosition { component, .. } => *component,
+            BallastPositionCommand::Home { component } => *component,
+        }
+    }
+}
@@ -1,39 +1,281 @@
-use super::{ COMMAND_QUEUE, Module };
+use super::{
+    AutopilotCommand,
+    BallastPositionCommand,
+    COMMAND_QUEUE,
+    Command,
+    CommandSource,
+    MissionCommand,
+    SystemCommand,
+    Target,
+};
+use crate::control::VehicleMode;
+use crate::hardware_model::ComponentKind;
 
 pub fn dispatch_next_command(sub: &mut crate::Submarine) {
     let wrapper = COMMAND_QUEUE.lock().unwrap().pop_front();
 
     match wrapper {
-        Some(w) => {
-            match w.module {
+        Some(w) => dispatch_command(sub, &w.command, CommandSource::Operator),
+        None => {}
+    }
+}
 
-                Module::Ballast => {
-                    let cmd =
-                        std::mem::ManuallyDrop::into_inner(
-                            unsafe{w.command.ballast}
-                        );
+pub fn dispatch_command(
+    sub: &mut crate::Submarine,
+    command: &Command,
+    source: CommandSource,
+) {
+    let mode = sub.mode().get_mode();
+    if !mode.accepts(source, command) {
+        eprintln!("Rejected {:?} command in {:?} mode.", source, mode);
+        sub.mode_mut().reject_command();
+        return;
+    }
+    if sub.get_emergency_stop().is_some() && command.actuates() {
+        eprintln!("Rejected {:?} command: emergency stop is latched.", source);
+        sub.mode_mut().reject_command();
+        return;
+    }
+    if sub.envelope().restricts(command) {
+        eprintln!("Rejected {:?} command: the envelope is breached.", source);
+        sub.mode_mut().reject_command();
+        return;
+    }
+    if sub.auto_trim().map_or(false, |t| t.restricts(command)) {
+        eprintln!("Rejected {:?} command: auto-trim is running.", source);
+        sub.mode_mut().reject_command();
+        return;
+    }
 
-                    sub.ballast.handle_command(cmd.as_ref());
-                },
-                Module::Light => {
-                    let cmd =
-                        std::mem::ManuallyDrop::into_inner(
-                            unsafe{w.command.light}
-                        );
+    match (command, command.target()) {
+        (Command::System(cmd), _) => handle_system_command(sub, cmd),
+        (Command::Autopilot(cmd), _) => handle_autopilot_command(sub, cmd),
+        (Command::Mission(cmd), _) => handle_mission_command(sub, cmd),
+        (cmd, Target::Component(id, kind)) =>
+            dispatch_to_component(sub, cmd, id, kind),
+        (cmd, _) => dispatch_to_components(sub, cmd),
+    }
+}
 
-                    sub.light.handle_command(cmd.as_ref());
-                },
-                Module::Propulsion => {
-                    let cmd =
-                        std::mem::ManuallyDrop::into_inner(
-                            unsafe{w.command.propulsion}
-                        );
+/*
+ * Module commands go to every present component of the command's kind.
+ * Disabled components reject them.
+ */
+fn dispatch_to_components(sub: &mut crate::Submarine, cmd: &Command) {
+    let Target::Kind(kind) = cmd.target() else { return };
+    let mut handled = false;
+
+    for slot in sub.components_mut().slots_mut() {
+        let name = slot.name.clone();
+        let Some(c) = slot.component_mut() else { continue };
+        if c.kind() != kind { continue; }
+
+        if c.is_enabled() {
+            c.handle(cmd);
+        } else {
+            eprintln!("Rejected command for {}: module is disabled.", name);
+        }
+        handled = true;
+    }
+
+    if !handled {
+        eprintln!("Rejected {:?} command: module is not present.", kind);
+    }
+}
+
+fn dispatch_to_component(
+    sub: &mut crate::Submarine,
+    cmd: &Command,
+    id: u8,
+    kind: ComponentKind,
+) {
+    let Some(slot) = sub.components_mut().get_mut(id) else {
+        eprintln!("Rejected command for module {}: no such module.", id);
+        return;
+    };
+    let name = slot.name.clone();
+
+    if slot.kind != kind {
+        eprintln!("Rejected {:?} command for {}: module is a {:?}.", kind, name, slot.kind);
+        return;
+    }
 
-                    sub.propulsion.handle_command(cmd.as_ref());
+    match slot.component_mut() {
+        Some(c) if c.is_enabled() => c.handle(cmd),
+        Some(_) => eprintln!("Rejected command for {}: module is disabled.", name),
+        None => eprintln!("Rejected command for {}: module is not present.", name),
+    }
+}
+
+fn handle_system_command(sub: &mut crate::Submarine, cmd: &SystemCommand) {
+    match cmd {
+        SystemCommand::EnableModule(id) => {
+            match sub.components_mut().get_mut(*id) {
+                Some(slot) => {
+                    let name = slot.name.clone();
+                    match slot.component_mut() {
+                        Some(c) => {
+                            println!("Enabling {}", name);
+                            c.enable();
+                        },
+                        None => eprintln!("Cannot enable {}: module is not present.", name),
+                    }
+                },
+                None => eprintln!("Cannot enable module {}: no such module.", id),
+            }
+        },
+        SystemCommand::DisableModule(id) => {
+            match sub.components_mut().get_mut(*id) {
+                Some(slot) => {
+                    let name = slot.name.clone();
+                    match slot.component_mut() {
+                        Some(c) => {
+                            println!("Disabling {}", name);
+                            c.disable();
+                        },
+                        None => eprintln!("Cannot disable {}: module is not present.", name),
+                    }
+                },
+                None => eprintln!("Cannot disable module {}: no such module.", id),
+            }
+        },
+        SystemCommand::SetMode(mode) => {
+            match VehicleMode::from_u8(*mode) {
+                Some(m) => if let Err(e) = sub.set_mode(m, "operator command") {
+                    eprintln!("Cannot change mode: {}", e);
                 },
+                None => eprintln!("Cannot change mode: no such mode {}.", mode),
+            }
+        },
+        // handled by the listener
+        SystemCommand::Heartbeat => {},
+        SystemCommand::EmergencyStop => sub.emergency_stop(CommandSource::Operator),
+        SystemCommand::ResetEmergencyStop => sub.reset_emergency_stop(),
+    }
+}
 
+fn handle_autopilot_command(sub: &mut crate::Submarine, cmd: &AutopilotCommand) {
+    match cmd {
+        AutopilotCommand::EngageDepthHold(depth) => {
+            if sub.is_actuation_inhibited() || !sub.mode().get_mode().allows_autopilot() {
+                eprintln!("Cannot engage depth hold in this mode.");
+                return;
+            }
+            if sub.depth_hold().is_none() {
+                eprintln!("Cannot engage depth hold: not configured.");
+                return;
             }
+            sub.abort_auto_trim();
+            if let Some(d) = sub.depth_hold_mut() { d.engage(*depth); }
+        },
+        AutopilotCommand::DisengageDepthHold => sub.disengage_depth_hold(),
+        AutopilotCommand::SetDepthGain(gain, value) => {
+            match sub.depth_hold_mut().map(|d| d.set_gain(*gain, *value)) {
+                Some(Ok(())) => {},
+                Some(Err(e)) => eprintln!("Cannot tune depth hold: {}", e),
+                None => eprintln!("Cannot tune depth hold: not configured."),
+            }
+        },
+        AutopilotCommand::SetDepthLimit(limit, value) => {
+            match sub.depth_hold_mut().map(|d| d.set_limit(*limit, *value)) {
+                Some(Ok(())) => {},
+                Some(Err(e)) => eprintln!("Cannot tune depth hold: {}", e),
+                None => eprintln!("Cannot tune depth hold: not configured."),
+            }
+        },
+        AutopilotCommand::SetDepthDeadband(value) => {
+            match sub.depth_hold_mut().map(|d| d.set_deadband(*value)) {
+                Some(Ok(())) => {},
+                Some(Err(e)) => eprintln!("Cannot tune depth hold: {}", e),
+                None => eprintln!("Cannot tune depth hold: not configured."),
+            }
+        },
+        AutopilotCommand::EngageHeadingHold(heading) => {
+            if sub.is_actuation_inhibited() || !sub.mode().get_mode().allows_autopilot() {
+                eprintln!("Cannot engage heading hold in this mode.");
+                return;
+            }
+            match sub.heading_hold_mut() {
+                Some(h) => h.engage(*heading),
+                None => eprintln!("Cannot engage heading hold: not configured."),
+            }
+        },
+        AutopilotCommand::DisengageHeadingHold => sub.disengage_heading_hold(),
+        AutopilotCommand::SetHeadingGain(gain, value) => {
+            match sub.heading_hold_mut().map(|h| h.set_gain(*gain, *value)) {
+                Some(Ok(())) => {},
+                Some(Err(e)) => eprintln!("Cannot tune heading hold: {}", e),
+                None => eprintln!("Cannot tune heading hold: not configured."),
+            }
+        },
+        AutopilotCommand::SetHeadingLimit(limit, value) => {
+            match sub.heading_hold_mut().map(|h| h.set_limit(*limit, *value)) {
+                Some(Ok(())) => {},
+                Some(Err(e)) => eprintln!("Cannot tune heading hold: {}", e),
+                None => eprintln!("Cannot tune heading hold: not configured."),
+            }
+        },
+        AutopilotCommand::StartAutoTrim => {
+            if sub.is_actuation_inhibited() || !sub.mode().get_mode().allows_autopilot() {
+                eprintln!("Cannot start auto-trim in this mode.");
+                return;
+            }
+            if let Err(e) = sub.start_auto_trim() {
+                eprintln!("Cannot start auto-trim: {}", e);
+            }
+        },
+        AutopilotCommand::AbortAutoTrim => sub.abort_auto_trim(),
+        AutopilotCommand::ApplyTrim => {
+            let position = sub.auto_trim()
+                .and_then(|t| t.get_calibration())
+                .and_then(|c| c.position);
+            let Some(position) = position else {
+                eprintln!("Cannot apply trim: no stored trim position.");
+                return;
+            };
+            let ballasts: Vec<u8> = sub.components().slots().iter()
+                .filter(|s| s.kind == ComponentKind::Ballast && s.component().is_some())
+                .map(|s| s.id)
+                .collect();
+            for id in ballasts {
+                let command = Command::BallastPosition(BallastPositionCommand::SetPosition {
+                    component: id,
+                    position,
+                });
+                dispatch_command(sub, &command, CommandSource::Controller);
+            }
+        },
+    }
+}
+
+fn handle_mission_command(sub: &mut crate::Submarine, cmd: &MissionCommand) {
+    let autonomous = sub.mode().get_mode() == VehicleMode::Autonomous;
+
+    match cmd {
+        MissionCommand::Start(index) => {
+            if !autonomous {
+                eprintln!("Cannot start mission: not in autonomous mode.");
+                return;
+            }
+            if let Err(e) = sub.missions_mut().start(*index as usize) {
+                eprintln!("Cannot start mission: {}", e);
+            }
+        },
+        MissionCommand::Pause => {
+            let actions = sub.missions_mut().pause();
+            sub.run_mission_actions(actions);
+        },
+        MissionCommand::Resume => {
+            if !autonomous {
+                eprintln!("Cannot resume mission: not in autonomous mode.");
+                return;
+            }
+            let actions = sub.missions_mut().resume();
+            sub.run_mission_actions(actions);
+        },
+        MissionCommand::Abort => {
+            let actions = sub.missions_mut().abort("operator command");
+            sub.run_mission_actions(actions);
         },
-        None => {}
     }
 }
@@ -4,21 +4,43 @@ use super::{
     Command,
     CommandDispatchWrapper,
     COMMAND_QUEUE,
+    EMERGENCY_STOP_PENDING,
+    LAST_HEARTBEAT,
+    LISTENER_HEALTHY,
     Module,
+    autopilot::{ AutopilotCommand, AUTOPILOT_MODULE_ID },
+    ballast::{ BallastPositionCommand, BALLAST_POSITION_MODULE_ID },
+    mission::{ MissionCommand, MISSION_MODULE_ID },
+    servo::{ ServoCommand, SERVO_MODULE_ID },
+    system::{ SystemCommand, SYSTEM_MODULE_ID },
 };
 use common::commands::*;
 use common::commands::serde::*;
-use std::mem::ManuallyDrop;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
 
 pub async fn listen(socket: &str) {
     println!("Listening for commands.");
-    let command_socket: UnixListener = 
-        UnixListener::bind(socket)
-        .expect("Failed to get command socket.");
+    let command_socket: UnixListener = match UnixListener::bind(socket) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to get command socket: {}", e);
+            LISTENER_HEALTHY.store(false, Ordering::SeqCst);
+            return;
+        }
+    };
+    LISTENER_HEALTHY.store(true, Ordering::SeqCst);
 
     loop {
-        if let Ok((mut s, _)) = command_socket.accept().await {
-            ingest_command(&mut s).await;
+        match command_socket.accept().await {
+            Ok((mut s, _)) => {
+                LISTENER_HEALTHY.store(true, Ordering::SeqCst);
+                ingest_command(&mut s).await;
+            },
+            Err(e) => {
+                eprintln!("Failed to accept command connection: {}", e);
+                LISTENER_HEALTHY.store(false, Ordering::SeqCst);
+            }
         }
     }
 }
@@ -26,8 +48,13 @@ pub async fn listen(socket: &str) {
 async fn ingest_command(s: &mut UnixStream) {
     let mut buf = [0; COMMAND_BUFFER_SIZE];
 
-    let len = s.read(&mut buf).await
-        .unwrap();
+    let len = match s.read(&mut buf).await {
+        Ok(len) => len,
+        Err(e) => {
+            eprintln!("Failed to read command socket: {}", e);
+            return;
+        }
+    };
 
     if len == 0 { println!("Empty socket"); return; }
 
@@ -36,17 +63,97 @@ async fn ingest_command(s: &mut UnixStream) {
         return;
     }
 
+    let payload: &[u8] = &buf[2..COMMAND_BUFFER_SIZE-1];
+
+    if buf[1] == SYSTEM_MODULE_ID {
+        let dispatchable_command = match SystemCommand::deserialize(payload) {
+            // heartbeats are timed on arrival, so a backed up queue can't
+            // make the link look lost
+            Ok(SystemCommand::Heartbeat) => {
+                *LAST_HEARTBEAT.lock().unwrap() = Some(Instant::now());
+                return;
+            },
+            Ok(SystemCommand::EmergencyStop) => {
+                EMERGENCY_STOP_PENDING.store(true, Ordering::SeqCst);
+                return;
+            },
+            Ok(c) => {
+                CommandDispatchWrapper {
+                    command: Command::System(c)
+                }
+            },
+            Err(_) => return
+        };
+
+        COMMAND_QUEUE.lock().unwrap().push_back(dispatchable_command);
+        return;
+    }
+
+    if buf[1] == AUTOPILOT_MODULE_ID {
+        let dispatchable_command = match AutopilotCommand::deserialize(payload) {
+            Ok(c) => {
+                CommandDispatchWrapper {
+                    command: Command::Autopilot(c)
+                }
+            },
+            Err(_) => return
+        };
+
+        COMMAND_QUEUE.lock().unwrap().push_back(dispatchable_command);
+        return;
+    }
+
+    if buf[1] == MISSION_MODULE_ID {
+        let dispatchable_command = match MissionCommand::deserialize(payload) {
+            Ok(c) => {
+                CommandDispatchWrapper {
+                    command: Command::Mission(c)
+                }
+            },
+            Err(_) => return
+        };
+
+        COMMAND_QUEUE.lock().unwrap().push_back(dispatchable_command);
+        return;
+    }
+
+    if buf[1] == BALLAST_POSITION_MODULE_ID {
+        let dispatchable_command = match BallastPositionCommand::deserialize(payload) {
+            Ok(c) => {
+                CommandDispatchWrapper {
+                    command: Command::BallastPosition(c)
+                }
+            },
+            Err(_) => return
+        };
+
+        COMMAND_QUEUE.lock().unwrap().push_back(dispatchable_command);
+        return;
+    }
+
+    if buf[1] == SERVO_MODULE_ID {
+        let dispatchable_command = match ServoCommand::deserialize(payload) {
+            Ok(c) => {
+                CommandDispatchWrapper {
+                    command: Command::Servo(c)
+                }
+            },
+            Err(_) => return
+        };
+
+        COMMAND_QUEUE.lock().unwrap().push_back(dispatchable_command);
+        return;
+    }
+
     let dispatchable_command = match MODULE_IDS.get(&buf[1]) {
         Some(m) => {
-            let payload: &[u8] = &buf[2..COMMAND_BUFFER_SIZE-1];
             match m {
 
                 Module::Ballast => {
                     match BallastCommand::deserialize(payload) {
                         Ok(c) => {
                             CommandDispatchWrapper {
-                                module: Module::Ballast,
-                                command: Command{ballast: ManuallyDrop::new(c)}
+                                command: Command::Ballast(c)
                             }
                         },
                         Err(_) => return
@@ -56,8 +163,7 @@ async fn ingest_command(s: &mut UnixStream) {
                     match LightCommand::deserialize(payload) {
                         Ok(c) => {
                             CommandDispatchWrapper {
-                                module: Module::Light,
-                                command: Command{light: ManuallyDrop::new(c)}
+                                command: Command::Light(c)
                             }
 
                         },
@@ -68,8 +174,7 @@ async fn ingest_command(s: &mut UnixStream) {
                     match PropulsionCommand::deserialize(payload) {
                         Ok(c) => {
                             CommandDispatchWrapper {
-                                module: Module::Propulsion,
-                                command: Command{propulsion: ManuallyDrop::new(c)}
+                                command: Command::Propulsion(c)
                             }
                         },
                         Err(_) => return
@@ -0,0 +1,36 @@
+/*
+ * Commands for the mission runner. Like system commands, these are not
+ * part of the common command set and use their own module ID.
+ *
+ * Payload layout: [opcode, args..]
+ */
+pub const MISSION_MODULE_ID: u8 = 0xB;
+
+const START_OPCODE: u8 = 0x0;
+const PAUSE_OPCODE: u8 = 0x1;
+const RESUME_OPCODE: u8 = 0x2;
+const ABORT_OPCODE: u8 = 0x3;
+
+#[derive(Debug, Clone, Copy)]
+pub enum MissionCommand {
+    // argument is the mission's index in the control config
+    Start(u8),
+    Pause,
+    Resume,
+    Abort,
+}
+
+impl MissionCommand {
+    pub fn deserialize(payload: &[u8]) -> Result<Self, ()> {
+        let opcode = *payload.get(0).ok_or(())?;
+
+        match opcode {
+            START_OPCODE =>
+                Ok(MissionCommand::Start(*payload.get(1).ok_or(())?)),
+            PAUSE_OPCODE => Ok(MissionCommand::Pause),
+            RESUME_OPCODE => Ok(MissionCommand::Resume),
+            ABORT_OPCODE => Ok(MissionCommand::Abort),
+            _ => Err(()),
+        }
+    }
+}
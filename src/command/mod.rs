@@ -1,28 +1,111 @@
+mod autopilot;
+mod ballast;
 mod dispatch;
 mod listen;
+mod mission;
+mod servo;
+mod system;
 
 use std::collections::VecDeque;
-use std::sync::{ Arc, Mutex };
+use std::sync::{ Arc, Mutex, atomic::{ AtomicBool, Ordering } };
+use std::time::Instant;
 use once_cell::sync::Lazy;
 use std::fs::remove_file;
-use crate::config::CommandingConfig;
+use crate::{
+    config::CommandingConfig,
+    definitions::DirectionVector,
+    hardware_model::ComponentKind,
+};
 use common::commands::*;
 
+pub use autopilot::AutopilotCommand;
+pub use ballast::BallastPositionCommand;
+pub use mission::MissionCommand;
+pub use servo::ServoCommand;
+pub use system::SystemCommand;
 
 static COMMAND_QUEUE: Lazy<Arc<Mutex<VecDeque<CommandDispatchWrapper>>>> =
     Lazy::new(|| {
         Arc::new(Mutex::new(VecDeque::new()))
     });
 
+static LISTENER_HEALTHY: AtomicBool = AtomicBool::new(false);
+
+// set by the listener, so an E-stop doesn't wait behind queued commands
+static EMERGENCY_STOP_PENDING: AtomicBool = AtomicBool::new(false);
+
+// when the listener last got a heartbeat, None until the first one
+static LAST_HEARTBEAT: Lazy<Mutex<Option<Instant>>> =
+    Lazy::new(|| Mutex::new(None));
+
 struct CommandDispatchWrapper {
-    module: Module,
     command: Command,
 }
 
-pub union Command {
-    ballast: std::mem::ManuallyDrop<Arc<BallastCommand>>,
-    light: std::mem::ManuallyDrop<Arc<LightCommand>>,
-    propulsion: std::mem::ManuallyDrop<Arc<PropulsionCommand>>,
+pub enum Command {
+    Autopilot(AutopilotCommand),
+    Ballast(Arc<BallastCommand>),
+    BallastPosition(BallastPositionCommand),
+    Light(Arc<LightCommand>),
+    Mission(MissionCommand),
+    Propulsion(Arc<PropulsionCommand>),
+    Servo(ServoCommand),
+    System(SystemCommand),
+    // raised by the controller only, there is no wire format for it
+    Thrust(DirectionVector),
+}
+
+// where a command came from, which decides whether the mode accepts it
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CommandSource {
+    // the command socket
+    Operator = 0x0,
+    // the controller's own configured actions
+    Controller = 0x1,
+    // a step of a running mission
+    Mission = 0x2,
+}
+
+pub enum Target {
+    // every component of this kind
+    Kind(ComponentKind),
+    // a single component, by ID, which must be of the given kind
+    Component(u8, ComponentKind),
+    // the controller itself
+    Controller,
+}
+
+impl Command {
+    pub fn target(&self) -> Target {
+        match self {
+            Command::Ballast(_) => Target::Kind(ComponentKind::Ballast),
+            Command::BallastPosition(c) =>
+                Target::Component(c.component(), ComponentKind::Ballast),
+            Command::Light(_) => Target::Kind(ComponentKind::Light),
+            Command::Propulsion(_) | Command::Thrust(_) =>
+                Target::Kind(ComponentKind::Propulsion),
+            Command::Servo(c) => Target::Component(c.component, ComponentKind::Servo),
+            Command::Autopilot(_) | Command::Mission(_) | Command::System(_) =>
+                Target::Controller,
+        }
+    }
+
+    /*
+     * Commands that move the vehicle or change its buoyancy, which an
+     * E-stop rejects.
+     */
+    pub fn actuates(&self) -> bool {
+        match self {
+            Command::Ballast(_)
+            | Command::BallastPosition(_)
+            | Command::Propulsion(_)
+            | Command::Servo(_)
+            | Command::Thrust(_) => true,
+            Command::Autopilot(c) => c.engages(),
+            Command::Mission(c) => matches!(c, MissionCommand::Start(_) | MissionCommand::Resume),
+            Command::Light(_) | Command::System(_) => false,
+        }
+    }
 }
 
 pub fn start_command_listener(config: &CommandingConfig) {
@@ -35,6 +118,28 @@ pub fn start_command_listener(config: &CommandingConfig) {
     });
 }
 
+pub fn is_listener_healthy() -> bool {
+    LISTENER_HEALTHY.load(Ordering::SeqCst)
+}
+
+pub fn last_heartbeat() -> Option<Instant> {
+    *LAST_HEARTBEAT.lock().unwrap()
+}
+
 pub fn dispatch(sub: &mut crate::Submarine) {
+    if EMERGENCY_STOP_PENDING.swap(false, Ordering::SeqCst) {
+        sub.emergency_stop(CommandSource::Operator);
+    }
     dispatch::dispatch_next_command(sub);
 }
+
+/*
+ * Applies a command raised by the controller itself, bypassing the queue.
+ */
+pub fn dispatch_command(
+    sub: &mut crate::Submarine,
+    command: &Command,
+    source: CommandSource,
+) {
+    dispatch::dispatch_command(sub, command, source);
+}
@@ -0,0 +1,35 @@
+/*
+ * Control surface commands. Like system commands, these are not part of
+ * the common command set and use their own module ID.
+ *
+ * Payload layout: [opcode, component ID, args..]
+ */
+pub const SERVO_MODULE_ID: u8 = 0xE;
+
+const SET_ANGLE_OPCODE: u8 = 0x0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ServoCommand {
+    // ID of the servo in the component registry
+    pub component: u8,
+    pub angle_deg: f32,
+}
+
+impl ServoCommand {
+    pub fn deserialize(payload: &[u8]) -> Result<Self, ()> {
+        let opcode = *payload.get(0).ok_or(())?;
+        let component = *payload.get(1).ok_or(())?;
+
+        match opcode {
+            SET_ANGLE_OPCODE => {
+                let angle: [u8; 4] = payload.get(2..6).ok_or(())?
+                    .try_into().map_err(|_| ())?;
+                let angle_deg = f32::from_le_bytes(angle);
+                if !angle_deg.is_finite() { return Err(()); }
+
+                Ok(ServoCommand { component, angle_deg })
+            },
+            _ => Err(()),
+        }
+    }
+}
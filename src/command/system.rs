@@ -0,0 +1,52 @@
+/*
+ * Commands handled by the controller itself rather than by a single
+ * hardware module. These are not part of the common command set, so they
+ * are addressed with their own module ID and decoded here.
+ *
+ * Payload layout: [opcode, args..]
+ */
+pub const SYSTEM_MODULE_ID: u8 = 0xF;
+
+const ENABLE_MODULE_OPCODE: u8 = 0x0;
+const DISABLE_MODULE_OPCODE: u8 = 0x1;
+const SET_MODE_OPCODE: u8 = 0x2;
+const HEARTBEAT_OPCODE: u8 = 0x3;
+const EMERGENCY_STOP_OPCODE: u8 = 0x4;
+const RESET_EMERGENCY_STOP_OPCODE: u8 = 0x5;
+
+#[derive(Debug, Clone, Copy)]
+pub enum SystemCommand {
+    // argument is the component ID, its index in the registry
+    EnableModule(u8),
+    DisableModule(u8),
+    // argument is a VehicleMode
+    SetMode(u8),
+    // tells the controller the topside is still there, never queued
+    Heartbeat,
+    // handled ahead of the queue, never queued
+    EmergencyStop,
+    ResetEmergencyStop,
+}
+
+impl SystemCommand {
+    pub fn deserialize(payload: &[u8]) -> Result<Self, ()> {
+        let opcode = *payload.get(0).ok_or(())?;
+
+        match opcode {
+            ENABLE_MODULE_OPCODE =>
+                Ok(SystemCommand::EnableModule(u8_arg(payload, 1)?)),
+            DISABLE_MODULE_OPCODE =>
+                Ok(SystemCommand::DisableModule(u8_arg(payload, 1)?)),
+            SET_MODE_OPCODE =>
+                Ok(SystemCommand::SetMode(u8_arg(payload, 1)?)),
+            HEARTBEAT_OPCODE => Ok(SystemCommand::Heartbeat),
+            EMERGENCY_STOP_OPCODE => Ok(SystemCommand::EmergencyStop),
+            RESET_EMERGENCY_STOP_OPCODE => Ok(SystemCommand::ResetEmergencyStop),
+            _ => Err(()),
+        }
+    }
+}
+
+fn u8_arg(payload: &[u8], idx: usize) -> Result<u8, ()> {
+    payload.get(idx).copied().ok_or(())
+}
@@ -0,0 +1,172 @@
+use serde::Deserialize;
+use crate::{
+    action::{ Action, BallastAction, LightAction },
+    config::{ mission::MissionConfig, rules::RuleConfig },
+    control::VehicleMode,
+};
+
+/*
+ * Closed loop controllers. A controller that is left out can't be
+ * engaged.
+ */
+#[derive(Debug, Deserialize)]
+pub struct ControlConfig {
+    #[serde(default = "default_initial_mode")]
+    pub initial_mode: VehicleMode,
+    pub depth_hold: Option<DepthHoldConfig>,
+    pub heading_hold: Option<HeadingHoldConfig>,
+    pub auto_trim: Option<AutoTrimConfig>,
+    // without this the link is not monitored
+    pub link: Option<LinkConfig>,
+    #[serde(default)]
+    pub envelope: EnvelopeConfig,
+    // without this no position is estimated
+    pub dead_reckoning: Option<DeadReckoningConfig>,
+    #[serde(default)]
+    pub failsafe: FailsafeConfig,
+    // started by their index in this list
+    #[serde(default)]
+    pub mission_files: Vec<String>,
+    // loaded from mission_files
+    #[serde(skip)]
+    pub missions: Vec<MissionConfig>,
+    #[serde(default)]
+    pub rules: Vec<RuleConfig>,
+}
+
+fn default_initial_mode() -> VehicleMode {
+    VehicleMode::Disarmed
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct PidConfig {
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+    // bound on the integral term's contribution to the output
+    pub integral_limit: f32,
+    // the output is clamped to +/- this
+    pub output_limit: f32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DepthHoldConfig {
+    pub pid: PidConfig,
+    // outputs smaller than this leave the ballast idle
+    pub deadband: f32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HeadingHoldConfig {
+    // error in degrees, output is the yaw part of the direction vector
+    pub pid: PidConfig,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LinkConfig {
+    // the vehicle fails safe when no heartbeat arrives for this long
+    pub timeout_ms: u32,
+}
+
+/*
+ * Trimming pulses the ballast until the depth rate settles under
+ * neutral_rate_mps. The pulse is halved each time the vehicle goes from
+ * sinking to rising or back.
+ */
+#[derive(Debug, Deserialize, Clone)]
+pub struct AutoTrimConfig {
+    // time to let the vehicle settle after a pulse
+    pub settle_s: f32,
+    // time the depth rate is measured over
+    pub measure_s: f32,
+    pub neutral_rate_mps: f32,
+    pub initial_pulse_s: f32,
+    pub min_pulse_s: f32,
+    pub max_pulses: u8,
+    // shallower than this the vehicle is floating, so it needs water
+    pub min_depth_m: f32,
+    // the result is kept here for later dives
+    pub calibration_file: String,
+}
+
+/*
+ * Hard limits, enforced whatever the commands say. Crossing a warning
+ * level is only reported; crossing a limit forces the vehicle to surface.
+ * Left out limits are not checked.
+ */
+#[derive(Debug, Deserialize, Clone)]
+pub struct EnvelopeConfig {
+    pub max_depth_m: Option<LimitConfig>,
+    // time spent below surface_depth_m in one dive
+    pub max_dive_s: Option<LimitConfig>,
+    // total time any thruster has been on since startup
+    pub max_thruster_on_s: Option<LimitConfig>,
+    // warn is above limit here, the voltage falls towards them
+    pub min_battery_v: Option<LimitConfig>,
+    // shallower than this the vehicle is surfaced
+    #[serde(default = "default_surface_depth")]
+    pub surface_depth_m: f32,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct LimitConfig {
+    pub warn: f32,
+    pub limit: f32,
+}
+
+fn default_surface_depth() -> f32 {
+    0.3
+}
+
+impl Default for EnvelopeConfig {
+    fn default() -> Self {
+        Self {
+            max_depth_m: None,
+            max_dive_s: None,
+            max_thruster_on_s: None,
+            min_battery_v: None,
+            surface_depth_m: default_surface_depth(),
+        }
+    }
+}
+
+/*
+ * Speed model for dead reckoning: speed follows the commanded forward
+ * thrust through a first order lag.
+ */
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct DeadReckoningConfig {
+    // speed through the water with the aft thruster running
+    pub full_speed_mps: f32,
+    // time for the speed to get most of the way to a new thrust
+    pub speed_time_constant_s: f32,
+    // turn rate with a side thruster running, only used without an IMU
+    pub full_turn_rate_dps: f32,
+    // error added per meter travelled, with and without an IMU heading
+    pub error_per_m: f32,
+    pub error_per_m_no_imu: f32,
+    // error of a GPS fix, which the estimate is reset to
+    pub gps_error_m: f32,
+}
+
+/*
+ * Run on entering Failsafe, whatever the cause. The thrusters are always
+ * stopped and the autopilots disengaged first.
+ */
+#[derive(Debug, Deserialize)]
+pub struct FailsafeConfig {
+    pub actions: Vec<Action>,
+}
+
+impl Default for FailsafeConfig {
+    // surface and show where the vehicle is
+    fn default() -> Self {
+        Self {
+            actions: vec![
+                Action::StopThrusters,
+                Action::Ballast { state: BallastAction::Discharge },
+                Action::Light { state: LightAction::Beacon },
+            ],
+        }
+    }
+}
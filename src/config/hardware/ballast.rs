@@ -2,7 +2,19 @@ use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
 pub struct BallastConfig {
-    pub gpio: BallastGpioConfig
+    pub name: String,
+    pub required: bool,
+    pub driver: BallastDriverConfig,
+    pub limit_switches: Option<BallastLimitSwitchConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BallastDriverConfig {
+    // a pair of pumps, one for intake and one for discharge
+    Pump(BallastGpioConfig),
+    // a syringe driven by a stepper through an A4988/DRV8825 style driver
+    Stepper(BallastStepperConfig),
 }
 
 #[derive(Debug, Deserialize)]
@@ -10,3 +22,33 @@ pub struct BallastGpioConfig {
     pub intake_pin: u8,
     pub discharge_pin: u8,
 }
+
+/*
+ * Positions are in steps from the home switch, which sits at the empty
+ * end of the syringe.
+ */
+#[derive(Debug, Deserialize)]
+pub struct BallastStepperConfig {
+    pub step_pin: u8,
+    pub dir_pin: u8,
+    pub enable_pin: u8,
+    pub home_pin: u8,
+    pub home_active_low: bool,
+    // set if a positive dir pin level moves towards empty
+    pub invert_direction: bool,
+    // position of a full syringe
+    pub max_position: u32,
+    // steps per second
+    pub max_speed: f32,
+    pub homing_speed: f32,
+    // steps per second squared
+    pub acceleration: f32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BallastLimitSwitchConfig {
+    pub full_pin: Option<u8>,
+    pub empty_pin: Option<u8>,
+    pub active_low: bool,
+    pub debounce_ticks: u8,
+}
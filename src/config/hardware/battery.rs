@@ -0,0 +1,13 @@
+use serde::Deserialize;
+
+/*
+ * An INA219 on the main battery lead.
+ */
+#[derive(Debug, Deserialize)]
+pub struct BatteryConfig {
+    pub name: String,
+    pub required: bool,
+    pub bus: u8,
+    pub address: u16,
+    pub shunt_ohms: f32,
+}
@@ -2,10 +2,12 @@ use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
 pub struct DebugConfig {
-    gpio: DebugGpioConfig,
+    pub name: String,
+    pub required: bool,
+    pub gpio: DebugGpioConfig,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct DebugGpioConfig {
-    debug_led_pin: u8,
+    pub debug_led_pin: u8,
 }
@@ -0,0 +1,14 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct DepthSensorConfig {
+    pub name: String,
+    pub required: bool,
+    pub bus: u8,
+    pub address: u16,
+    // kg/m^3, about 997 for fresh water and 1029 for sea water
+    pub fluid_density: f32,
+    // pressure at the surface; when left out, the first reading is taken
+    // as the surface, so the vehicle must start out at the surface
+    pub surface_pressure_mbar: Option<f32>,
+}
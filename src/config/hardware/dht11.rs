@@ -2,11 +2,24 @@ use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
 pub struct Dht11Config {
+    pub name: String,
+    pub required: bool,
     pub gpio: Dht11GpioConfig,
     pub sample_interval: u8,
+    pub condensation: CondensationConfig,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Dht11GpioConfig {
     pub data_pin: u8,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct CondensationConfig {
+    // alarm when the air is within this many degrees of its dew point
+    pub dew_point_spread_alarm_c: f32,
+    // alarm when relative humidity rises faster than this, in % per minute
+    pub humidity_rate_alarm: f32,
+    // number of samples the humidity rate is computed over
+    pub rate_window_samples: u8,
+}
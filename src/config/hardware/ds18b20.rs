@@ -0,0 +1,17 @@
+use serde::Deserialize;
+
+/*
+ * One DS18B20 probe on the 1-Wire bus, identified by its w1 device name,
+ * such as "28-0316a2795aff".
+ */
+#[derive(Debug, Deserialize)]
+pub struct Ds18b20Config {
+    pub name: String,
+    pub required: bool,
+    pub address: String,
+    // w1 devices directory, normally /sys/bus/w1/devices
+    pub w1_root: String,
+    pub sample_interval_ms: u32,
+    // failed reads in a row before the reading is no longer valid
+    pub max_failures: u8,
+}
@@ -0,0 +1,17 @@
+use serde::Deserialize;
+use super::serial::SerialConfig;
+
+#[derive(Debug, Deserialize)]
+pub struct EchoSounderConfig {
+    pub name: String,
+    pub required: bool,
+    pub serial: SerialConfig,
+    // ticks between distance requests
+    pub poll_interval_ticks: u8,
+    // ticks without a reply before the range is considered stale
+    pub timeout_ticks: u8,
+    // alarm when closer to the bottom than this, in meters
+    pub min_altitude_m: f32,
+    // readings below this confidence, in percent, don't raise the alarm
+    pub min_confidence: u8,
+}
@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use super::serial::SerialConfig;
+
+#[derive(Debug, Deserialize)]
+pub struct GpsConfig {
+    pub name: String,
+    pub required: bool,
+    pub serial: SerialConfig,
+    // ticks without a valid fix before the fix is reported as lost
+    pub timeout_ticks: u8,
+    // also set the system clock from the first valid fix
+    pub set_system_clock: bool,
+}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct ImuConfig {
+    pub name: String,
+    pub required: bool,
+    pub bus: u8,
+    pub address: u16,
+    // added to the sensor's heading to account for how it is mounted
+    pub heading_offset_deg: f32,
+}
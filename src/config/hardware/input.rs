@@ -0,0 +1,26 @@
+use serde::Deserialize;
+use crate::action::Action;
+
+#[derive(Debug, Deserialize)]
+pub struct InputConfig {
+    pub name: String,
+    pub required: bool,
+    pub pin: u8,
+    pub pull: InputPull,
+    pub active_low: bool,
+    // edges closer together than this are bounce, 0 counts every edge
+    pub debounce_ms: u32,
+    // run when the input becomes active, and when it becomes inactive
+    #[serde(default)]
+    pub on_activate: Vec<Action>,
+    #[serde(default)]
+    pub on_deactivate: Vec<Action>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum InputPull {
+    Up,
+    Down,
+    None,
+}
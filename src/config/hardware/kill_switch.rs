@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+/*
+ * A reed switch or pull-plug that has to be in place for the actuators to
+ * run. The vehicle is armed while the input is active.
+ */
+#[derive(Debug, Deserialize)]
+pub struct KillSwitchConfig {
+    pub pin: u8,
+    pub active_low: bool,
+    pub debounce_ticks: u8,
+}
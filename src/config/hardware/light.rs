@@ -2,6 +2,8 @@ use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
 pub struct LightConfig {
+    pub name: String,
+    pub required: bool,
     pub gpio: LightGpioConfig,
 }
 
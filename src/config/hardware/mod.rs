@@ -1,18 +1,67 @@
 pub mod ballast;
+pub mod battery;
+pub mod debug;
+pub mod depth_sensor;
+pub mod echo_sounder;
+pub mod gps;
+pub mod imu;
+pub mod input;
+pub mod kill_switch;
 pub mod light;
 pub mod propulsion;
+pub mod serial;
+pub mod servo;
 pub mod dht11;
+pub mod ds18b20;
 
 use serde::Deserialize;
 use ballast::BallastConfig;
+use battery::BatteryConfig;
+use debug::DebugConfig;
+use depth_sensor::DepthSensorConfig;
+use echo_sounder::EchoSounderConfig;
+use gps::GpsConfig;
+use imu::ImuConfig;
+use input::InputConfig;
+use kill_switch::KillSwitchConfig;
 use light::LightConfig;
 use propulsion::PropulsionConfig;
+use servo::ServoConfig;
 use dht11::Dht11Config;
+use ds18b20::Ds18b20Config;
 
+/*
+ * Each component type is a list, so several instances of the same
+ * peripheral can be configured. Component names must be unique.
+ */
 #[derive(Debug, Deserialize)]
 pub struct HardwareConfig {
-    pub ballast: BallastConfig,
-    pub light: LightConfig,
-    pub propulsion: PropulsionConfig,
-    pub dht11: Dht11Config,
+    #[serde(default)]
+    pub ballast: Vec<BallastConfig>,
+    #[serde(default)]
+    pub debug: Vec<DebugConfig>,
+    #[serde(default)]
+    pub light: Vec<LightConfig>,
+    #[serde(default)]
+    pub propulsion: Vec<PropulsionConfig>,
+    #[serde(default)]
+    pub dht11: Vec<Dht11Config>,
+    #[serde(default)]
+    pub ds18b20: Vec<Ds18b20Config>,
+    #[serde(default)]
+    pub servo: Vec<ServoConfig>,
+    #[serde(default)]
+    pub echo_sounder: Vec<EchoSounderConfig>,
+    #[serde(default)]
+    pub gps: Vec<GpsConfig>,
+    #[serde(default)]
+    pub input: Vec<InputConfig>,
+    #[serde(default)]
+    pub depth_sensor: Vec<DepthSensorConfig>,
+    #[serde(default)]
+    pub imu: Vec<ImuConfig>,
+    #[serde(default)]
+    pub battery: Vec<BatteryConfig>,
+    // without a kill switch the vehicle is always armed
+    pub kill_switch: Option<KillSwitchConfig>,
 }
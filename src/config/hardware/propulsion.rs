@@ -2,9 +2,10 @@ use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
 pub struct PropulsionConfig {
+    pub name: String,
+    pub required: bool,
     pub gpio: PropulsionGpioConfig,
-    pub thrust_step_up: f64,
-    pub thrust_step_down: f64,
+    pub current_sense: Option<CurrentSenseConfig>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -13,3 +14,41 @@ pub struct PropulsionGpioConfig {
     pub starboard_pin: u8,
     pub port_pin: u8,
 }
+
+/*
+ * Currents are in amps and apply to every thruster. Thrusters without a
+ * sensor are not monitored.
+ */
+#[derive(Debug, Deserialize)]
+pub struct CurrentSenseConfig {
+    // above this while on, the prop is stalled
+    pub stall_current: f32,
+    // below this while on, the motor circuit is open
+    pub open_circuit_current: f32,
+    // consecutive ticks out of range before a fault is raised
+    pub fault_ticks: u8,
+    // ticks after switching on during which inrush current is ignored
+    pub spin_up_ticks: u8,
+    pub aft: Option<CurrentSensorConfig>,
+    pub starboard: Option<CurrentSensorConfig>,
+    pub port: Option<CurrentSensorConfig>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CurrentSensorConfig {
+    Ina219 {
+        bus: u8,
+        address: u16,
+        shunt_ohms: f32,
+    },
+    // a current sense amplifier read through an MCP3008 ADC
+    Adc {
+        spi_bus: u8,
+        chip_select: u8,
+        channel: u8,
+        reference_v: f32,
+        zero_offset_v: f32,
+        volts_per_amp: f32,
+    },
+}
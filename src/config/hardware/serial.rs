@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+/*
+ * A serial device, 8N1. Any tty works, including a pseudo-terminal
+ * standing in for the real device.
+ */
+#[derive(Debug, Deserialize)]
+pub struct SerialConfig {
+    pub device: String,
+    pub baud: u32,
+}
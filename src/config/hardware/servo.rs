@@ -0,0 +1,32 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct ServoConfig {
+    pub name: String,
+    pub required: bool,
+    pub driver: ServoDriverConfig,
+    // pulse widths at the two ends of the servo's range
+    pub min_pulse_us: u32,
+    pub max_pulse_us: u32,
+    // angle swept between min_pulse_us and max_pulse_us
+    pub pulse_range_deg: f32,
+    pub center_trim_us: i32,
+    // travel limits of the control surface
+    pub min_angle_deg: f32,
+    pub max_angle_deg: f32,
+    // largest change in angle per tick
+    pub max_step_deg: f32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServoDriverConfig {
+    Pwm {
+        channel: u8,
+    },
+    Pca9685 {
+        bus: u8,
+        address: u16,
+        channel: u8,
+    },
+}
@@ -0,0 +1,105 @@
+use serde::Deserialize;
+use std::fs;
+use crate::action::{ Action, BallastAction, LightAction };
+
+/*
+ * A mission file: steps run in order, and the mission is aborted when any
+ * of the abort conditions is met or a wait times out.
+ */
+#[derive(Debug, Deserialize, Clone)]
+pub struct MissionConfig {
+    pub name: String,
+    pub steps: Vec<MissionStep>,
+    #[serde(default)]
+    pub abort_when: Vec<MissionCondition>,
+    // run after the running step is stopped
+    #[serde(default = "default_on_abort")]
+    pub on_abort: Vec<Action>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MissionStep {
+    // thrust is zeroed when the step ends
+    Thrust { x: f32, y: f32, duration_s: f32 },
+    // with a duration the ballast is idled when the step ends, without
+    // one it is left running
+    Ballast {
+        state: BallastAction,
+        #[serde(default)]
+        duration_s: f32,
+    },
+    Light { state: LightAction },
+    Wait { duration_s: f32 },
+    // aborts the mission if the condition isn't met in time
+    WaitUntil { condition: MissionCondition, timeout_s: Option<f32> },
+    Action { action: Action },
+}
+
+/*
+ * Sensors are looked up by kind, the first with a reading is used.
+ * Without a reading the condition is not met.
+ */
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MissionCondition {
+    DepthGreaterThan { m: f32 },
+    DepthLessThan { m: f32 },
+    AltitudeLessThan { m: f32 },
+    InputActive { component: String },
+}
+
+fn default_on_abort() -> Vec<Action> {
+    vec![Action::StopThrusters]
+}
+
+impl MissionConfig {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let h = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to open mission file {}: {}", path, e))?;
+
+        toml::from_str::<MissionConfig>(h.as_str())
+            .map_err(|e| format!("Invalid mission file {}: {}", path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_mission(contents: &str) -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mission.toml");
+        fs::write(&path, contents).unwrap();
+        (dir, path.to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn loads_a_mission() {
+        let (_dir, path) = write_mission(r#"
+            name = "short"
+
+            [[steps]]
+            type = "wait"
+            duration_s = 2.0
+        "#);
+
+        let mission = MissionConfig::load(&path).unwrap();
+        assert_eq!(mission.name, "short");
+        assert_eq!(mission.steps.len(), 1);
+        assert_eq!(mission.on_abort.len(), 1);
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        let (_dir, path) = write_mission(r#"
+            name = "short"
+
+            [[steps]]
+            type = "hover"
+        "#);
+        assert!(MissionConfig::load(&path).is_err());
+
+        assert!(MissionConfig::load("/nonexistent/mission.toml").is_err());
+    }
+}
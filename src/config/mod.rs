@@ -1,4 +1,7 @@
+pub mod control;
 pub mod hardware;
+pub mod mission;
+pub mod rules;
 pub mod telemetry;
 
 use serde::Deserialize;
@@ -11,6 +14,7 @@ pub struct Config {
     pub system: SystemConfig,
     pub commanding: CommandingConfig,
     pub hardware: hardware::HardwareConfig,
+    pub control: control::ControlConfig,
     pub telemetry: telemetry::TelemetryConfig,
 }
 
@@ -25,13 +29,18 @@ pub struct CommandingConfig {
 }
 
 impl Config {
-    pub fn load() -> Self {
+    pub fn load() -> Result<Self, String> {
         let h = fs::read_to_string(CONFIG_FILE)
-            .expect(format!(
-                "Failed to open config file: {}",
-                CONFIG_FILE).as_str()
-            );
+            .map_err(|e| format!("Failed to open config file {}: {}", CONFIG_FILE, e))?;
 
-        toml::from_str::<Config>(h.as_str()).unwrap()
+        let mut config = toml::from_str::<Config>(h.as_str())
+            .map_err(|e| format!("Invalid config file {}: {}", CONFIG_FILE, e))?;
+
+        // missions are started by index, so a bad one can't just be left out
+        config.control.missions = config.control.mission_files.iter()
+            .map(|path| mission::MissionConfig::load(path))
+            .collect::<Result<_, _>>()?;
+
+        Ok(config)
     }
 }
@@ -0,0 +1,51 @@
+use serde::Deserialize;
+use crate::action::Action;
+
+/*
+ * A reactive behaviour: when a value crosses its threshold the on_enter
+ * actions run, and when it is back past the threshold by the hysteresis
+ * the on_exit actions run.
+ */
+#[derive(Debug, Deserialize, Clone)]
+pub struct RuleConfig {
+    pub name: String,
+    pub when: RuleCondition,
+    #[serde(default)]
+    pub hysteresis: f32,
+    #[serde(default)]
+    pub on_enter: Vec<Action>,
+    #[serde(default)]
+    pub on_exit: Vec<Action>,
+}
+
+/*
+ * Met while the value is above `above` and below `below`; give one for a
+ * threshold or both for a band. The value is read from the named
+ * component, or from the first of its kind with a reading.
+ */
+#[derive(Debug, Deserialize, Clone)]
+pub struct RuleCondition {
+    pub value: RuleValue,
+    pub component: Option<String>,
+    pub above: Option<f32>,
+    pub below: Option<f32>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleValue {
+    // DHT11, percent and degrees C
+    Humidity,
+    InternalTemperature,
+    // DS18B20, degrees C
+    WaterTemperature,
+    // meters
+    Depth,
+    Altitude,
+    // degrees
+    Heading,
+    Roll,
+    Pitch,
+    // volts
+    BatteryVoltage,
+}
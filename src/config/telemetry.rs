@@ -2,5 +2,15 @@ use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
 pub struct TelemetryConfig {
-    pub socket: String
+    pub socket: String,
+    pub host: HostTelemetryConfig,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HostTelemetryConfig {
+    pub sample_interval_ms: u32,
+    pub sys_root: String,
+    pub proc_root: String,
+    // mount point of the partition logs are written to
+    pub log_partition: String,
 }
@@ -0,0 +1,250 @@
+use serde::{ Deserialize, Serialize };
+use std::fs;
+use crate::{
+    action::BallastAction,
+    command::{ AutopilotCommand, Command },
+    config::control::AutoTrimConfig,
+    hardware_model::{ Ballast, ComponentRegistry, DepthSensor },
+    traits::{ Component, SubmarineComponent },
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TrimState {
+    Idle = 0x0,
+    Settling = 0x1,
+    Measuring = 0x2,
+    Pulsing = 0x3,
+    Complete = 0x4,
+    Failed = 0x5,
+    Aborted = 0x6,
+}
+
+/*
+ * The ballast level found to be neutral. A stepper ballast gives its
+ * position; a pump ballast only knows how long it pumped, in seconds of
+ * intake over discharge since the trim started.
+ */
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct TrimCalibration {
+    pub position: Option<u32>,
+    pub pump_seconds: Option<f32>,
+}
+
+/*
+ * One-shot search for neutral buoyancy: settle, measure the depth rate,
+ * then pulse the ballast against it and repeat. The thrusters should be
+ * off while trimming, or the depth rate means nothing.
+ */
+pub struct AutoTrim {
+    config: AutoTrimConfig,
+    dt: f32,
+    state: TrimState,
+    elapsed_s: f32,
+    pulses: u8,
+    pulse_s: f32,
+    // 1 sinking, -1 rising, 0 before the first measurement
+    last_direction: i8,
+    measure_start_m: f32,
+    depth_rate_mps: f32,
+    pump_seconds: f32,
+    calibration: Option<TrimCalibration>,
+}
+
+impl AutoTrim {
+    pub fn new(config: &AutoTrimConfig, dt: f32) -> Self {
+        Self {
+            config: config.clone(),
+            dt,
+            state: TrimState::Idle,
+            elapsed_s: 0.0,
+            pulses: 0,
+            pulse_s: config.initial_pulse_s,
+            last_direction: 0,
+            measure_start_m: 0.0,
+            depth_rate_mps: 0.0,
+            pump_seconds: 0.0,
+            calibration: load_calibration(&config.calibration_file),
+        }
+    }
+
+    pub fn start(&mut self, components: &mut ComponentRegistry) {
+        if self.is_running() { return; }
+
+        println!("Auto-trim started.");
+        self.state = TrimState::Settling;
+        self.elapsed_s = 0.0;
+        self.pulses = 0;
+        self.pulse_s = self.config.initial_pulse_s;
+        self.last_direction = 0;
+        self.depth_rate_mps = 0.0;
+        self.pump_seconds = 0.0;
+        command_ballast(components, BallastAction::Idle);
+    }
+
+    pub fn abort(&mut self, components: &mut ComponentRegistry) {
+        if !self.is_running() { return; }
+
+        println!("Auto-trim aborted.");
+        self.state = TrimState::Aborted;
+        command_ballast(components, BallastAction::Idle);
+    }
+
+    pub fn update(&mut self, components: &mut ComponentRegistry) {
+        if !self.is_running() { return; }
+
+        let Some(depth) = components.iter_of::<DepthSensor>()
+            .filter(|s| s.is_enabled())
+            .find_map(|s| s.get_depth())
+        else {
+            self.fail(components, "lost the depth reading");
+            return;
+        };
+
+        self.elapsed_s += self.dt;
+
+        match self.state {
+            TrimState::Settling => if self.elapsed_s >= self.config.settle_s {
+                self.state = TrimState::Measuring;
+                self.elapsed_s = 0.0;
+                self.measure_start_m = depth;
+            },
+            TrimState::Measuring => if self.elapsed_s >= self.config.measure_s {
+                self.depth_rate_mps = (depth - self.measure_start_m) / self.elapsed_s;
+                self.elapsed_s = 0.0;
+                self.adjust(components, depth);
+            },
+            TrimState::Pulsing => if self.elapsed_s >= self.pulse_s {
+                command_ballast(components, BallastAction::Idle);
+                self.state = TrimState::Settling;
+                self.elapsed_s = 0.0;
+            },
+            _ => {},
+        }
+    }
+
+    fn adjust(&mut self, components: &mut ComponentRegistry, depth: f32) {
+        // floating at the surface reads as neutral, but needs water
+        let floating = depth < self.config.min_depth_m;
+        if !floating && self.depth_rate_mps.abs() < self.config.neutral_rate_mps {
+            self.finish(components);
+            return;
+        }
+
+        let direction: i8 = if !floating && self.depth_rate_mps > 0.0 { 1 } else { -1 };
+        if self.last_direction != 0 && direction != self.last_direction {
+            self.pulse_s = (self.pulse_s / 2.0).max(self.config.min_pulse_s);
+        }
+        self.last_direction = direction;
+
+        if self.pulses >= self.config.max_pulses {
+            self.fail(components, "no neutral point within the pulse limit");
+            return;
+        }
+        self.pulses += 1;
+
+        let at_limit = components.iter_of::<Ballast>().any(|b| {
+            if direction > 0 { b.is_empty() } else { b.is_full() }
+        });
+        if at_limit {
+            self.fail(components, "the ballast is at its limit");
+            return;
+        }
+
+        let (action, level) = if direction > 0 {
+            (BallastAction::Discharge, -self.pulse_s)
+        } else {
+            (BallastAction::Intake, self.pulse_s)
+        };
+        command_ballast(components, action);
+        self.pump_seconds += level;
+        self.state = TrimState::Pulsing;
+    }
+
+    fn finish(&mut self, components: &mut ComponentRegistry) {
+        let position = components.iter_of::<Ballast>().find_map(|b| b.get_position());
+        let calibration = TrimCalibration {
+            position,
+            pump_seconds: if position.is_none() { Some(self.pump_seconds) } else { None },
+        };
+
+        println!("Auto-trim complete: {:?}", calibration);
+        if let Err(e) = save_calibration(&self.config.calibration_file, &calibration) {
+            eprintln!("Failed to save trim calibration: {}", e);
+        }
+        self.calibration = Some(calibration);
+        self.state = TrimState::Complete;
+    }
+
+    fn fail(&mut self, components: &mut ComponentRegistry, reason: &str) {
+        eprintln!("Auto-trim failed: {}", reason);
+        self.state = TrimState::Failed;
+        command_ballast(components, BallastAction::Idle);
+    }
+
+    // the thrusters stay off while trimming, stopping is still allowed
+    pub fn restricts(&self, command: &Command) -> bool {
+        if !self.is_running() { return false; }
+
+        match command {
+            Command::Propulsion(_) => true,
+            Command::Thrust(v) => v.x != 0.0 || v.y != 0.0,
+            // heading hold steers with the thrusters
+            Command::Autopilot(AutopilotCommand::EngageHeadingHold(_)) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self.state, TrimState::Settling | TrimState::Measuring | TrimState::Pulsing)
+    }
+
+    pub fn get_state(&self) -> TrimState {
+        self.state
+    }
+
+    pub fn get_pulses(&self) -> u8 {
+        self.pulses
+    }
+
+    pub fn get_pulse_length(&self) -> f32 {
+        self.pulse_s
+    }
+
+    pub fn get_depth_rate(&self) -> f32 {
+        self.depth_rate_mps
+    }
+
+    pub fn get_calibration(&self) -> Option<TrimCalibration> {
+        self.calibration
+    }
+}
+
+fn command_ballast(components: &mut ComponentRegistry, action: BallastAction) {
+    let command = action.to_command();
+
+    for ballast in components.iter_mut_of::<Ballast>() {
+        if ballast.is_enabled() { ballast.handle(&command); }
+    }
+}
+
+// a missing file just means the vehicle hasn't been trimmed yet
+fn load_calibration(path: &str) -> Option<TrimCalibration> {
+    let contents = fs::read_to_string(path).ok()?;
+
+    match toml::from_str::<TrimCalibration>(contents.as_str()) {
+        Ok(c) => {
+            println!("Loaded trim calibration: {:?}", c);
+            Some(c)
+        },
+        Err(e) => {
+            eprintln!("Invalid trim calibration in {}: {}", path, e);
+            None
+        }
+    }
+}
+
+fn save_calibration(path: &str, calibration: &TrimCalibration) -> Result<(), String> {
+    let contents = toml::to_string(calibration).map_err(|e| e.to_string())?;
+
+    fs::write(path, contents).map_err(|e| e.to_string())
+}
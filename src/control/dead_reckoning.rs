@@ -0,0 +1,116 @@
+use crate::{
+    config::control::DeadReckoningConfig,
+    hardware_model::{ ComponentRegistry, Gps, Imu, Propulsion },
+    traits::SubmarineComponent,
+};
+
+/*
+ * Estimates the position from the running thrusters and the heading, in
+ * meters north and east of the last GPS fix. While the GPS has a fix, at
+ * the surface, the estimate is held at the fix, so submerged it is
+ * relative to where the vehicle dived. The error only grows until the
+ * next fix; currents are not modelled at all.
+ */
+pub struct DeadReckoning {
+    config: DeadReckoningConfig,
+    dt: f32,
+    north_m: f32,
+    east_m: f32,
+    speed_mps: f32,
+    // degrees clockwise from north, or from the starting heading without
+    // an IMU
+    heading_deg: f32,
+    error_m: f32,
+    imu_heading: bool,
+    fixed: bool,
+}
+
+impl DeadReckoning {
+    pub fn new(config: &DeadReckoningConfig, dt: f32) -> Self {
+        Self {
+            config: *config,
+            dt,
+            north_m: 0.0,
+            east_m: 0.0,
+            speed_mps: 0.0,
+            heading_deg: 0.0,
+            error_m: config.gps_error_m,
+            imu_heading: false,
+            fixed: false,
+        }
+    }
+
+    pub fn update(&mut self, components: &ComponentRegistry) {
+        // the thrusters are on or off, whatever the commanded vector is,
+        // and a stalled one isn't pushing
+        let running = components.iter_of::<Propulsion>()
+            .filter(|p| p.is_enabled())
+            .map(|p| (p.get_aft_state(), p.get_sb_state(), p.get_port_state()))
+            .next();
+        let (yaw, thrust) = running.map_or((0.0, 0.0), |(aft, sb, port)| {
+            let yaw = if sb { 1.0 } else { 0.0 } - if port { 1.0 } else { 0.0 };
+            (yaw, if aft { 1.0 } else { 0.0 })
+        });
+
+        let target = self.config.full_speed_mps * thrust;
+        let tau = self.config.speed_time_constant_s.max(self.dt);
+        self.speed_mps += (target - self.speed_mps) * self.dt / tau;
+
+        let imu_heading = components.iter_of::<Imu>()
+            .filter(|i| i.is_enabled())
+            .find_map(|i| i.get_heading());
+        self.imu_heading = imu_heading.is_some();
+        self.heading_deg = match imu_heading {
+            Some(h) => h,
+            None => (self.heading_deg + self.config.full_turn_rate_dps * yaw * self.dt)
+                .rem_euclid(360.0),
+        };
+
+        self.fixed = components.iter_of::<Gps>()
+            .any(|g| g.is_enabled() && g.has_fix());
+        if self.fixed {
+            self.north_m = 0.0;
+            self.east_m = 0.0;
+            self.error_m = self.config.gps_error_m;
+            return;
+        }
+
+        let distance = self.speed_mps * self.dt;
+        let heading = self.heading_deg.to_radians();
+        self.north_m += distance * heading.cos();
+        self.east_m += distance * heading.sin();
+        self.error_m += distance.abs() * if self.imu_heading {
+            self.config.error_per_m
+        } else {
+            self.config.error_per_m_no_imu
+        };
+    }
+
+    pub fn get_north(&self) -> f32 {
+        self.north_m
+    }
+
+    pub fn get_east(&self) -> f32 {
+        self.east_m
+    }
+
+    pub fn get_error(&self) -> f32 {
+        self.error_m
+    }
+
+    pub fn get_heading(&self) -> f32 {
+        self.heading_deg
+    }
+
+    pub fn get_speed(&self) -> f32 {
+        self.speed_mps
+    }
+
+    pub fn has_imu_heading(&self) -> bool {
+        self.imu_heading
+    }
+
+    pub fn has_gps_fix(&self) -> bool {
+        self.fixed
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    action::BallastAction,
+    config::control::DepthHoldConfig,
+    error::{ InitFailure, PeripheralInitError },
+    hardware_model::{ Ballast, ComponentRegistry, DepthSensor },
+    traits::{ Component, SubmarineComponent },
+};
+use super::pid::{ Gain, Limit, Pid };
+
+/*
+ * Holds a target depth with the ballast. A positive output means the
+ * vehicle needs to go deeper, so it takes on water. The ballast is only
+ * commanded when the wanted state changes, since every ballast command
+ * restarts its state transition.
+ *
+ * There is no vertical thruster yet, so the ballast is the only actuator.
+ */
+pub struct DepthHold {
+    pid: Pid,
+    deadband: f32,
+    dt: f32,
+    setpoint: Option<f32>,
+    depth: Option<f32>,
+    error: f32,
+    ballast_action: Option<BallastAction>,
+}
+
+impl DepthHold {
+    pub fn new(config: &DepthHoldConfig, dt: f32) -> Result<Self, PeripheralInitError> {
+        if !config.deadband.is_finite() || config.deadband < 0.0 {
+            return Err(PeripheralInitError {
+                reason: InitFailure::Config,
+                message: "Invalid depth hold deadband".to_string(),
+            });
+        }
+        let pid = Pid::new(config.pid).map_err(|e| PeripheralInitError {
+            reason: InitFailure::Config,
+            message: format!("Invalid depth hold PID: {}", e),
+        })?;
+
+        Ok(Self {
+            pid,
+            deadband: config.deadband,
+            dt,
+            setpoint: None,
+            depth: None,
+            error: 0.0,
+            ballast_action: None,
+        })
+    }
+
+    pub fn engage(&mut self, setpoint: f32) {
+        println!("Depth hold engaged at {:.2}m.", setpoint);
+
+        if self.setpoint.is_none() {
+            self.pid.reset();
+        }
+        self.setpoint = Some(setpoint);
+    }
+
+    pub fn disengage(&mut self, components: &mut ComponentRegistry) {
+        if self.setpoint.take().is_none() { return; }
+
+        println!("Depth hold disengaged.");
+        self.pid.reset();
+        self.error = 0.0;
+        self.command_ballast(components, BallastAction::Idle);
+        self.ballast_action = None;
+    }
+
+    pub fn set_gain(&mut self, gain: Gain, value: f32) -> Result<(), String> {
+        self.pid.set_gain(gain, value)?;
+        println!("Depth hold {:?} gain set to {}.", gain, value);
+
+        Ok(())
+    }
+
+    pub fn set_limit(&mut self, limit: Limit, value: f32) -> Result<(), String> {
+        self.pid.set_limit(limit, value)?;
+        println!("Depth hold {:?} limit set to {}.", limit, value);
+
+        Ok(())
+    }
+
+    pub fn set_deadband(&mut self, value: f32) -> Result<(), String> {
+        if !value.is_finite() || value < 0.0 {
+            return Err(format!("{} is not a valid deadband", value));
+        }
+        self.deadband = value;
+        println!("Depth hold deadband set to {}.", value);
+
+        Ok(())
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.setpoint.is_some()
+    }
+
+    pub fn get_setpoint(&self) -> Option<f32> {
+        self.setpoint
+    }
+
+    pub fn get_depth(&self) -> Option<f32> {
+        self.depth
+    }
+
+    pub fn get_error(&self) -> f32 {
+        self.error
+    }
+
+    pub fn get_output(&self) -> f32 {
+        self.pid.get_output()
+    }
+
+    pub fn get_integral_term(&self) -> f32 {
+        self.pid.get_integral_term()
+    }
+
+    pub fn update(&mut self, components: &mut ComponentRegistry) {
+        self.depth = components.iter_of::<DepthSensor>()
+            .filter(|s| s.is_enabled())
+            .find_map(|s| s.get_depth());
+
+        let Some(setpoint) = self.setpoint else { return };
+        let Some(depth) = self.depth else {
+            eprintln!("Depth hold lost its depth reading.");
+            self.disengage(components);
+            return;
+        };
+
+        self.error = setpoint - depth;
+        let output = self.pid.update(self.error, self.dt);
+
+        let action = if output > self.deadband {
+            BallastAction::Intake
+        } else if output < -self.deadband {
+            BallastAction::Discharge
+        } else {
+            BallastAction::Idle
+        };
+
+        if self.ballast_action != Some(action) {
+            self.command_ballast(components, action);
+            self.ballast_action = Some(action);
+        }
+    }
+
+    fn command_ballast(&self, components: &mut ComponentRegistry, action: BallastAction) {
+        let command = action.to_command();
+
+        for ballast in components.iter_mut_of::<Ballast>() {
+            if ballast.is_enabled() { ballast.handle(&command); }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{
+    command::Command,
+    config::control::{ EnvelopeConfig, LimitConfig },
+    hardware_model::{ Battery, ComponentRegistry, DepthSensor, Propulsion },
+};
+use common::commands::BallastCommand;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LimitStatus {
+    Ok = 0x0,
+    Warning = 0x1,
+    Breached = 0x2,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Limit {
+    Depth = 0x0,
+    DiveTime = 0x1,
+    ThrusterTime = 0x2,
+    Battery = 0x3,
+}
+
+const LIMITS: [Limit; 4] = [Limit::Depth, Limit::DiveTime, Limit::ThrusterTime, Limit::Battery];
+
+/*
+ * Checks the vehicle against its operating envelope every tick. A breach
+ * holds until the value is back inside the warning level, so a vehicle
+ * bobbing at the limit doesn't toggle in and out of it.
+ */
+pub struct Envelope {
+    config: EnvelopeConfig,
+    dt: f32,
+    depth_m: Option<f32>,
+    dive_s: f32,
+    thruster_on_s: f32,
+    battery_v: Option<f32>,
+    battery_a: Option<f32>,
+    status: [LimitStatus; 4],
+}
+
+impl Envelope {
+    pub fn new(config: &EnvelopeConfig, dt: f32) -> Self {
+        Self {
+            config: config.clone(),
+            dt,
+            depth_m: None,
+            dive_s: 0.0,
+            thruster_on_s: 0.0,
+            battery_v: None,
+            battery_a: None,
+            status: [LimitStatus::Ok; 4],
+        }
+    }
+
+    /*
+     * Returns true only on the update that finds a new breach.
+     */
+    pub fn update(&mut self, components: &ComponentRegistry) -> bool {
+        self.depth_m = components.iter_of::<DepthSensor>().find_map(|d| d.get_depth());
+        let battery = components.iter_of::<Battery>().find(|b| b.get_voltage().is_some());
+        self.battery_v = battery.and_then(|b| b.get_voltage());
+        self.battery_a = battery.and_then(|b| b.get_current());
+
+        match self.depth_m {
+            Some(d) if d > self.config.surface_depth_m => self.dive_s += self.dt,
+            Some(_) => self.dive_s = 0.0,
+            // keep counting through a sensor dropout
+            None => if self.dive_s > 0.0 { self.dive_s += self.dt },
+        }
+
+        let thrusting = components.iter_of::<Propulsion>()
+            .any(|p| p.get_aft_state() || p.get_sb_state() || p.get_port_state());
+        if thrusting { self.thruster_on_s += self.dt; }
+
+        let mut new_breach = false;
+        for limit in LIMITS {
+            let previous = self.status[limit as usize];
+            let status = self.check(limit, previous);
+
+            if status != previous {
+                match status {
+                    LimitStatus::Warning if previous == LimitStatus::Ok =>
+                        eprintln!("Envelope warning: {:?} near its limit.", limit),
+                    LimitStatus::Breached => {
+                        eprintln!("Envelope breached: {:?} over its limit.", limit);
+                        new_breach = true;
+                    },
+                    _ => println!("Envelope: {:?} back to {:?}.", limit, status),
+                }
+            }
+            self.status[limit as usize] = status;
+        }
+
+        new_breach
+    }
+
+    fn check(&self, limit: Limit, previous: LimitStatus) -> LimitStatus {
+        let (config, value, falling) = match limit {
+            Limit::Depth => (self.config.max_depth_m, self.depth_m, false),
+            Limit::DiveTime => (self.config.max_dive_s, Some(self.dive_s), false),
+            Limit::ThrusterTime =>
+                (self.config.max_thruster_on_s, Some(self.thruster_on_s), false),
+            Limit::Battery => (self.config.min_battery_v, self.battery_v, true),
+        };
+        let (Some(LimitConfig { warn, limit: bound }), Some(value)) = (config, value) else {
+            return previous;
+        };
+
+        // battery limits are lower bounds, flip them to compare the same way
+        let (value, warn, bound) = if falling {
+            (-value, -warn, -bound)
+        } else {
+            (value, warn, bound)
+        };
+
+        if value >= bound {
+            LimitStatus::Breached
+        } else if value >= warn {
+            // a breach holds until back under the warning level
+            if previous == LimitStatus::Breached { previous } else { LimitStatus::Warning }
+        } else {
+            LimitStatus::Ok
+        }
+    }
+
+    pub fn is_breached(&self) -> bool {
+        self.status.iter().any(|s| *s == LimitStatus::Breached)
+    }
+
+    /*
+     * While breached, nothing may drive the vehicle or stop the ballast
+     * blowing.
+     */
+    pub fn restricts(&self, command: &Command) -> bool {
+        if !self.is_breached() { return false; }
+
+        match command {
+            Command::Propulsion(_) | Command::BallastPosition(_) => true,
+            // stopping is still allowed
+            Command::Thrust(v) => v.x != 0.0 || v.y != 0.0,
+            Command::Ballast(c) => !matches!(c.as_ref(), BallastCommand::Discharge),
+            Command::Autopilot(c) => c.engages(),
+            _ => false,
+        }
+    }
+
+    pub fn get_status(&self, limit: Limit) -> LimitStatus {
+        self.status[limit as usize]
+    }
+
+    pub fn get_dive_time(&self) -> f32 {
+        self.dive_s
+    }
+
+    pub fn get_thruster_time(&self) -> f32 {
+        self.thruster_on_s
+    }
+
+    pub fn get_battery_voltage(&self) -> Option<f32> {
+        self.battery_v
+    }
+
+    pub fn get_battery_current(&self) -> Option<f32> {
+        self.battery_a
+    }
+}
+
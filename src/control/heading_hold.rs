@@ -0,0 +1,113 @@
+use crate::{
+    config::control::HeadingHoldConfig,
+    error::{ InitFailure, PeripheralInitError },
+    hardware_model::{ ComponentRegistry, Imu, Propulsion },
+    traits::SubmarineComponent,
+};
+use super::pid::{ Gain, Limit, Pid };
+
+/*
+ * Holds a heading with the port and starboard thrusters. Only the yaw
+ * part of the pilot's direction vector is taken over, surge still comes
+ * from the pilot. A positive output turns to starboard.
+ */
+pub struct HeadingHold {
+    pid: Pid,
+    dt: f32,
+    setpoint: Option<f32>,
+    heading: Option<f32>,
+    error: f32,
+}
+
+impl HeadingHold {
+    pub fn new(config: &HeadingHoldConfig, dt: f32) -> Result<Self, PeripheralInitError> {
+        let pid = Pid::new(config.pid).map_err(|e| PeripheralInitError {
+            reason: InitFailure::Config,
+            message: format!("Invalid heading hold PID: {}", e),
+        })?;
+
+        Ok(Self {
+            pid,
+            dt,
+            setpoint: None,
+            heading: None,
+            error: 0.0,
+        })
+    }
+
+    pub fn engage(&mut self, setpoint: f32) {
+        let setpoint = setpoint.rem_euclid(360.0);
+        println!("Heading hold engaged at {:.1} degrees.", setpoint);
+
+        if self.setpoint.is_none() {
+            self.pid.reset();
+        }
+        self.setpoint = Some(setpoint);
+    }
+
+    pub fn disengage(&mut self, components: &mut ComponentRegistry) {
+        if self.setpoint.take().is_none() { return; }
+
+        println!("Heading hold disengaged.");
+        self.pid.reset();
+        self.error = 0.0;
+        for propulsion in components.iter_mut_of::<Propulsion>() {
+            propulsion.set_yaw_override(None);
+        }
+    }
+
+    pub fn set_gain(&mut self, gain: Gain, value: f32) -> Result<(), String> {
+        self.pid.set_gain(gain, value)?;
+        println!("Heading hold {:?} gain set to {}.", gain, value);
+
+        Ok(())
+    }
+
+    pub fn set_limit(&mut self, limit: Limit, value: f32) -> Result<(), String> {
+        self.pid.set_limit(limit, value)?;
+        println!("Heading hold {:?} limit set to {}.", limit, value);
+
+        Ok(())
+    }
+
+    pub fn get_setpoint(&self) -> Option<f32> {
+        self.setpoint
+    }
+
+    pub fn get_heading(&self) -> Option<f32> {
+        self.heading
+    }
+
+    pub fn get_error(&self) -> f32 {
+        self.error
+    }
+
+    pub fn get_output(&self) -> f32 {
+        self.pid.get_output()
+    }
+
+    pub fn get_integral_term(&self) -> f32 {
+        self.pid.get_integral_term()
+    }
+
+    pub fn update(&mut self, components: &mut ComponentRegistry) {
+        self.heading = components.iter_of::<Imu>()
+            .filter(|i| i.is_enabled())
+            .find_map(|i| i.get_heading());
+
+        let Some(setpoint) = self.setpoint else { return };
+        let Some(heading) = self.heading else {
+            eprintln!("Heading hold lost its heading reading.");
+            self.disengage(components);
+            return;
+        };
+
+        // the short way round, in (-180, 180]
+        self.error = 180.0 - (180.0 - (setpoint - heading)).rem_euclid(360.0);
+        let output = self.pid.update(self.error, self.dt);
+
+        for propulsion in components.iter_mut_of::<Propulsion>() {
+            propulsion.set_yaw_override(Some(output));
+        }
+    }
+}
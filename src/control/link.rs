@@ -0,0 +1,72 @@
+use std::time::{ Duration, Instant };
+use crate::config::control::LinkConfig;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LinkState {
+    // no heartbeat yet
+    Waiting = 0x0,
+    Up = 0x1,
+    Lost = 0x2,
+}
+
+/*
+ * Watches the topside heartbeat. The timeout also runs before the first
+ * heartbeat, counted from startup, so a topside that never connects is
+ * treated the same as one that drops out.
+ */
+pub struct LinkMonitor {
+    timeout: Duration,
+    started: Instant,
+    last_heartbeat: Option<Instant>,
+    state: LinkState,
+    losses: u16,
+}
+
+impl LinkMonitor {
+    pub fn new(config: &LinkConfig) -> Self {
+        Self {
+            timeout: Duration::from_millis(config.timeout_ms as u64),
+            started: Instant::now(),
+            last_heartbeat: None,
+            state: LinkState::Waiting,
+            losses: 0,
+        }
+    }
+
+    /*
+     * Takes the latest heartbeat time and returns true only on the update
+     * that finds the link lost.
+     */
+    pub fn update(&mut self, last_heartbeat: Option<Instant>) -> bool {
+        self.last_heartbeat = last_heartbeat;
+        let since = last_heartbeat.unwrap_or(self.started).elapsed();
+
+        if since < self.timeout {
+            if last_heartbeat.is_some() && self.state != LinkState::Up {
+                println!("Link up.");
+                self.state = LinkState::Up;
+            }
+            return false;
+        }
+
+        if self.state == LinkState::Lost { return false; }
+
+        eprintln!("Link lost: no heartbeat for {} ms.", since.as_millis());
+        self.state = LinkState::Lost;
+        self.losses = self.losses.wrapping_add(1);
+
+        true
+    }
+
+    pub fn get_state(&self) -> LinkState {
+        self.state
+    }
+
+    pub fn get_time_since_heartbeat(&self) -> Option<Duration> {
+        self.last_heartbeat.map(|t| t.elapsed())
+    }
+
+    pub fn get_losses(&self) -> u16 {
+        self.losses
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{
+    action::{ Action, BallastAction },
+    config::mission::{ MissionCondition, MissionConfig, MissionStep },
+    hardware_model::{ ComponentRegistry, DepthSensor, EchoSounder, Input },
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MissionState {
+    Idle = 0x0,
+    Running = 0x1,
+    Paused = 0x2,
+    Complete = 0x3,
+    Aborted = 0x4,
+}
+
+enum StepStatus {
+    Running,
+    Done,
+    TimedOut,
+}
+
+/*
+ * Steps through the loaded missions. The runner doesn't touch the
+ * hardware itself: it returns the actions to run, which the submarine
+ * dispatches like any other command, so a mission gets the same checks
+ * as the operator.
+ */
+pub struct MissionRunner {
+    missions: Vec<MissionConfig>,
+    dt: f32,
+    state: MissionState,
+    mission: Option<usize>,
+    step: usize,
+    step_started: bool,
+    step_elapsed_s: f32,
+}
+
+impl MissionRunner {
+    pub fn new(missions: Vec<MissionConfig>, dt: f32) -> Self {
+        for (i, m) in missions.iter().enumerate() {
+            println!("Loaded mission {}: {} ({} steps)", i, m.name, m.steps.len());
+        }
+
+        Self {
+            missions,
+            dt,
+            state: MissionState::Idle,
+            mission: None,
+            step: 0,
+            step_started: false,
+            step_elapsed_s: 0.0,
+        }
+    }
+
+    pub fn start(&mut self, index: usize) -> Result<(), String> {
+        if self.is_active() {
+            return Err("a mission is already running".to_string());
+        }
+        let Some(mission) = self.missions.get(index) else {
+            return Err(format!("no such mission {}", index));
+        };
+
+        println!("Starting mission {}.", mission.name);
+        self.state = MissionState::Running;
+        self.mission = Some(index);
+        self.step = 0;
+        self.step_started = false;
+        self.step_elapsed_s = 0.0;
+
+        Ok(())
+    }
+
+    // stops what the current step is doing, it is restarted on resume
+    pub fn pause(&mut self) -> Vec<Action> {
+        if self.state != MissionState::Running { return Vec::new(); }
+
+        println!("Mission paused.");
+        self.state = MissionState::Paused;
+        self.current_step().map_or(Vec::new(), |s| stop_actions(s))
+    }
+
+    pub fn resume(&mut self) -> Vec<Action> {
+        if self.state != MissionState::Paused { return Vec::new(); }
+
+        println!("Mission resumed.");
+        self.state = MissionState::Running;
+        match self.current_step() {
+            Some(s) if self.step_started => start_actions(s),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn abort(&mut self, reason: &str) -> Vec<Action> {
+        if !self.is_active() { return Vec::new(); }
+        let Some(mission) = self.mission.and_then(|i| self.missions.get(i)) else {
+            return Vec::new();
+        };
+
+        eprintln!("Mission {} aborted: {}", mission.name, reason);
+        let mut actions = self.current_step().map_or(Vec::new(), |s| stop_actions(s));
+        actions.extend(mission.on_abort.iter().cloned());
+        self.state = MissionState::Aborted;
+
+        actions
+    }
+
+    /*
+     * Runs the mission for one tick. Steps that finish straight away are
+     * chained, so a run of light or action steps takes a single tick.
+     */
+    pub fn update(&mut self, components: &ComponentRegistry) -> Vec<Action> {
+        if self.state != MissionState::Running { return Vec::new(); }
+        let Some(index) = self.mission else { return Vec::new() };
+
+        let abort = self.missions[index].abort_when.iter()
+            .find(|c| is_met(c, components))
+            .map(|c| format!("{:?}", c));
+        if let Some(reason) = abort {
+            return self.abort(&reason);
+        }
+
+        let mut actions = Vec::new();
+
+        while let Some(step) = self.missions[index].steps.get(self.step) {
+            if self.step_started {
+                self.step_elapsed_s += self.dt;
+            } else {
+                actions.extend(start_actions(step));
+                self.step_started = true;
+                self.step_elapsed_s = 0.0;
+            }
+
+            match progress(step, self.step_elapsed_s, components) {
+                StepStatus::Running => return actions,
+                StepStatus::TimedOut => {
+                    let reason = format!("step {} timed out", self.step);
+                    actions.extend(self.abort(&reason));
+                    return actions;
+                },
+                StepStatus::Done => {
+                    actions.extend(stop_actions(step));
+                    self.step += 1;
+                    self.step_started = false;
+                },
+            }
+        }
+
+        println!("Mission {} complete.", self.missions[index].name);
+        self.state = MissionState::Complete;
+
+        actions
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, MissionState::Running | MissionState::Paused)
+    }
+
+    pub fn get_state(&self) -> MissionState {
+        self.state
+    }
+
+    pub fn get_mission(&self) -> Option<usize> {
+        self.mission
+    }
+
+    pub fn get_step(&self) -> usize {
+        self.step
+    }
+
+    pub fn get_step_count(&self) -> usize {
+        self.mission.map_or(0, |i| self.missions[i].steps.len())
+    }
+
+    pub fn get_step_elapsed(&self) -> f32 {
+        self.step_elapsed_s
+    }
+
+    // None for steps without a fixed length
+    pub fn get_step_duration(&self) -> Option<f32> {
+        match self.current_step()? {
+            MissionStep::Thrust { duration_s, .. }
+            | MissionStep::Wait { duration_s } => Some(*duration_s),
+            MissionStep::Ballast { duration_s, .. } if *duration_s > 0.0 =>
+                Some(*duration_s),
+            _ => None,
+        }
+    }
+
+    fn current_step(&self) -> Option<&MissionStep> {
+        self.missions.get(self.mission?)?.steps.get(self.step)
+    }
+}
+
+fn start_actions(step: &MissionStep) -> Vec<Action> {
+    match step {
+        MissionStep::Thrust { x, y, .. } => vec![Action::Thrust { x: *x, y: *y }],
+        MissionStep::Ballast { state, .. } => vec![Action::Ballast { state: *state }],
+        MissionStep::Light { state } => vec![Action::Light { state: *state }],
+        MissionStep::Action { action } => vec![action.clone()],
+        MissionStep::Wait { .. } | MissionStep::WaitUntil { .. } => Vec::new(),
+    }
+}
+
+fn stop_actions(step: &MissionStep) -> Vec<Action> {
+    match step {
+        MissionStep::Thrust { .. } => vec![Action::Thrust { x: 0.0, y: 0.0 }],
+        MissionStep::Ballast { duration_s, .. } if *duration_s > 0.0 =>
+            vec![Action::Ballast { state: BallastAction::Idle }],
+        _ => Vec::new(),
+    }
+}
+
+fn progress(step: &MissionStep, elapsed_s: f32, components: &ComponentRegistry) -> StepStatus {
+    let done = match step {
+        MissionStep::Thrust { duration_s, .. }
+        | MissionStep::Ballast { duration_s, .. }
+        | MissionStep::Wait { duration_s } => elapsed_s >= *duration_s,
+        MissionStep::Light { .. } | MissionStep::Action { .. } => true,
+        MissionStep::WaitUntil { condition, timeout_s } => {
+            if is_met(condition, components) { return StepStatus::Done; }
+            if timeout_s.map_or(false, |t| elapsed_s >= t) { return StepStatus::TimedOut; }
+            false
+        },
+    };
+
+    if done { StepStatus::Done } else { StepStatus::Running }
+}
+
+fn is_met(condition: &MissionCondition, components: &ComponentRegistry) -> bool {
+    let depth = || components.iter_of::<DepthSensor>().find_map(|d| d.get_depth());
+    let altitude = || components.iter_of::<EchoSounder>()
+        .filter(|e| !e.is_stale())
+        .find_map(|e| e.get_range_m());
+
+    match condition {
+        MissionCondition::DepthGreaterThan { m } => depth().map_or(false, |d| d > *m),
+        MissionCondition::DepthLessThan { m } => depth().map_or(false, |d| d < *m),
+        MissionCondition::AltitudeLessThan { m } => altitude().map_or(false, |a| a < *m),
+        MissionCondition::InputActive { component } => components.find(component)
+            .and_then(|s| s.component())
+            .and_then(|c| c.as_any().downcast_ref::<Input>())
+            .map_or(false, |i| i.is_active()),
+    }
+}
@@ -0,0 +1,20 @@
+pub mod auto_trim;
+pub mod dead_reckoning;
+pub mod depth_hold;
+pub mod envelope;
+pub mod heading_hold;
+pub mod link;
+pub mod mission;
+pub mod mode;
+pub mod pid;
+pub mod rules;
+
+pub use auto_trim::AutoTrim;
+pub use dead_reckoning::DeadReckoning;
+pub use depth_hold::DepthHold;
+pub use envelope::{ Envelope, Limit, LimitStatus };
+pub use heading_hold::HeadingHold;
+pub use link::{ LinkMonitor, LinkState };
+pub use mission::MissionRunner;
+pub use mode::{ ModeMachine, VehicleMode };
+pub use rules::RuleEngine;
@@ -0,0 +1,146 @@
+use serde::Deserialize;
+use crate::command::{ Command, CommandSource };
+
+/*
+ * The vehicle's operating mode, which decides where commands are taken
+ * from and what may drive the actuators:
+ *   Disarmed: actuators are off, only system commands are taken
+ *   Manual: the operator drives, no autopilots
+ *   Assisted: the operator drives, with autopilots
+ *   Autonomous: autopilots and missions drive, the operator can only
+ *     take back control with system commands
+ *   Failsafe: the controller is recovering the vehicle, the operator can
+ *     only take back control with system commands
+ */
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VehicleMode {
+    Disarmed = 0x0,
+    Manual = 0x1,
+    Assisted = 0x2,
+    Autonomous = 0x3,
+    Failsafe = 0x4,
+}
+
+impl VehicleMode {
+    pub fn from_u8(mode: u8) -> Option<Self> {
+        match mode {
+            0x0 => Some(VehicleMode::Disarmed),
+            0x1 => Some(VehicleMode::Manual),
+            0x2 => Some(VehicleMode::Assisted),
+            0x3 => Some(VehicleMode::Autonomous),
+            0x4 => Some(VehicleMode::Failsafe),
+            _ => None,
+        }
+    }
+
+    /*
+     * Any mode can be disarmed or fail safe. Failsafe can only be left
+     * for manual control, and leaving Disarmed always goes through Manual.
+     */
+    pub fn can_transition_to(self, next: VehicleMode) -> bool {
+        use VehicleMode::*;
+
+        match (self, next) {
+            (a, b) if a == b => false,
+            (_, Disarmed) | (_, Failsafe) => true,
+            (Disarmed, Manual) => true,
+            (Disarmed, _) => false,
+            (Failsafe, Manual) => true,
+            (Failsafe, _) => false,
+            _ => true,
+        }
+    }
+
+    pub fn accepts(self, source: CommandSource, command: &Command) -> bool {
+        use VehicleMode::*;
+
+        // system commands are how the operator changes modes, so they are
+        // always taken from the operator, as are mission commands, which
+        // check the mode themselves
+        if source == CommandSource::Operator
+            && matches!(command, Command::System(_) | Command::Mission(_))
+        {
+            return true;
+        }
+
+        match (self, source) {
+            (Disarmed, _) => false,
+            (Manual, _) => !matches!(command, Command::Autopilot(c) if c.engages()),
+            (Assisted, _) => true,
+            (Autonomous, CommandSource::Operator) => false,
+            (Autonomous, _) => true,
+            (Failsafe, CommandSource::Operator) => false,
+            (Failsafe, _) => true,
+        }
+    }
+
+    pub fn allows_autopilot(self) -> bool {
+        matches!(self, VehicleMode::Assisted | VehicleMode::Autonomous)
+    }
+
+    pub fn inhibits_actuators(self) -> bool {
+        self == VehicleMode::Disarmed
+    }
+}
+
+pub struct ModeMachine {
+    mode: VehicleMode,
+    previous: VehicleMode,
+    ticks_in_mode: u32,
+    rejected_commands: u16,
+}
+
+impl ModeMachine {
+    pub fn new(initial: VehicleMode) -> Self {
+        println!("Starting in {:?} mode.", initial);
+
+        Self {
+            mode: initial,
+            previous: initial,
+            ticks_in_mode: 0,
+            rejected_commands: 0,
+        }
+    }
+
+    pub fn get_mode(&self) -> VehicleMode {
+        self.mode
+    }
+
+    pub fn get_previous_mode(&self) -> VehicleMode {
+        self.previous
+    }
+
+    pub fn get_ticks_in_mode(&self) -> u32 {
+        self.ticks_in_mode
+    }
+
+    pub fn get_rejected_commands(&self) -> u16 {
+        self.rejected_commands
+    }
+
+    pub fn reject_command(&mut self) {
+        self.rejected_commands = self.rejected_commands.wrapping_add(1);
+    }
+
+    /*
+     * Only changes the mode; the submarine runs the exit and entry
+     * actions.
+     */
+    pub fn transition(&mut self, next: VehicleMode, reason: &str) -> Result<(), String> {
+        if !self.mode.can_transition_to(next) {
+            return Err(format!("cannot go from {:?} to {:?}", self.mode, next));
+        }
+
+        println!("Mode {:?} -> {:?}: {}", self.mode, next, reason);
+        self.previous = self.mode;
+        self.mode = next;
+        self.ticks_in_mode = 0;
+
+        Ok(())
+    }
+
+    pub fn tick(&mut self) {
+        self.ticks_in_mode = self.ticks_in_mode.saturating_add(1);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::config::control::PidConfig;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Gain {
+    Proportional,
+    Integral,
+    Derivative,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Limit {
+    Integral,
+    Output,
+}
+
+/*
+ * PID controller run once per tick. Anti-windup is by conditional
+ * integration: while the output is saturated, the error is only
+ * integrated if it would bring the output back into range. The integral
+ * term is also clamped to integral_limit.
+ */
+#[derive(Debug)]
+pub struct Pid {
+    config: PidConfig,
+    integral: f32,
+    last_error: Option<f32>,
+    output: f32,
+}
+
+impl Pid {
+    pub fn new(config: PidConfig) -> Result<Self, String> {
+        if !(config.kp.is_finite() && config.ki.is_finite() && config.kd.is_finite()) {
+            return Err("gains must be finite".to_string());
+        }
+        // clamp panics on a negative or NaN bound
+        if !config.integral_limit.is_finite() || config.integral_limit < 0.0 {
+            return Err("integral_limit must be zero or more".to_string());
+        }
+        if !config.output_limit.is_finite() || config.output_limit <= 0.0 {
+            return Err("output_limit must be more than zero".to_string());
+        }
+
+        Ok(Self {
+            config,
+            integral: 0.0,
+            last_error: None,
+            output: 0.0,
+        })
+    }
+
+    pub fn update(&mut self, error: f32, dt: f32) -> f32 {
+        let c = &self.config;
+
+        let derivative = match self.last_error {
+            Some(last) if dt > 0.0 => (error - last) / dt,
+            _ => 0.0,
+        };
+        self.last_error = Some(error);
+
+        let proportional = c.kp * error;
+        let unclamped = proportional + c.ki * self.integral + c.kd * derivative;
+        let saturated = unclamped.abs() >= c.output_limit;
+        let winding_up = saturated && unclamped.signum() == error.signum();
+
+        if !winding_up && c.ki != 0.0 {
+            let limit = c.integral_limit / c.ki.abs();
+            self.integral = (self.integral + error * dt).clamp(-limit, limit);
+        }
+
+        self.output = (proportional + c.ki * self.integral + c.kd * derivative)
+            .clamp(-c.output_limit, c.output_limit);
+
+        self.output
+    }
+
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.last_error = None;
+        self.output = 0.0;
+    }
+
+    pub fn set_gain(&mut self, gain: Gain, value: f32) -> Result<(), String> {
+        if !value.is_finite() {
+            return Err(format!("{} is not a valid gain", value));
+        }
+
+        match gain {
+            Gain::Proportional => self.config.kp = value,
+            Gain::Integral => {
+                // keep the integral term's contribution where it was
+                if value != 0.0 {
+                    self.integral *= self.config.ki / value;
+                }
+                self.config.ki = value;
+            },
+            Gain::Derivative => self.config.kd = value,
+        }
+
+        Ok(())
+    }
+
+    pub fn set_limit(&mut self, limit: Limit, value: f32) -> Result<(), String> {
+        match limit {
+            Limit::Integral => {
+                if !value.is_finite() || value < 0.0 {
+                    return Err(format!("{} is not a valid integral limit", value));
+                }
+                self.config.integral_limit = value;
+            },
+            Limit::Output => {
+                if !value.is_finite() || value <= 0.0 {
+                    return Err(format!("{} is not a valid output limit", value));
+                }
+                self.config.output_limit = value;
+            },
+        }
+
+        Ok(())
+    }
+
+    pub fn get_output(&self) -> f32 {
+        self.output
+    }
+
+    pub fn get_integral_term(&self) -> f32 {
+        self.config.ki * self.integral
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pid(kp: f32, ki: f32, kd: f32, integral_limit: f32, output_limit: f32) -> Pid {
+        Pid::new(PidConfig { kp, ki, kd, integral_limit, output_limit }).unwrap()
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-5, "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn responds_in_proportion_to_the_error() {
+        let mut pid = pid(2.0, 0.0, 0.0, 0.0, 10.0);
+
+        assert_close(pid.update(1.5, 0.1), 3.0);
+        assert_close(pid.update(-2.0, 0.1), -4.0);
+        assert_close(pid.update(0.0, 0.1), 0.0);
+    }
+
+    #[test]
+    fn clamps_the_output() {
+        let mut pid = pid(10.0, 0.0, 0.0, 0.0, 1.0);
+
+        assert_close(pid.update(5.0, 0.1), 1.0);
+        assert_close(pid.update(-5.0, 0.1), -1.0);
+        assert_close(pid.get_output(), -1.0);
+    }
+
+    #[test]
+    fn clamps_the_integral_term() {
+        let mut pid = pid(0.0, 2.0, 0.0, 0.5, 10.0);
+
+        for _ in 0..100 { pid.update(1.0, 0.1); }
+        assert_close(pid.get_integral_term(), 0.5);
+
+        for _ in 0..100 { pid.update(-1.0, 0.1); }
+        assert_close(pid.get_integral_term(), -0.5);
+    }
+
+    #[test]
+    fn does_not_wind_up_while_saturated() {
+        let mut pid = pid(1.0, 1.0, 0.0, 100.0, 1.0);
+
+        for _ in 0..50 { pid.update(5.0, 1.0); }
+        assert_close(pid.get_integral_term(), 0.0);
+
+        // nothing to unwind, so the output follows the error straight away
+        assert_close(pid.update(-0.5, 1.0), -1.0);
+    }
+
+    #[test]
+    fn differentiates_the_error() {
+        let mut pid = pid(0.0, 0.0, 1.0, 0.0, 10.0);
+
+        // no derivative on the first update
+        assert_close(pid.update(1.0, 0.5), 0.0);
+        assert_close(pid.update(2.0, 0.5), 2.0);
+
+        pid.reset();
+        assert_close(pid.update(5.0, 0.5), 0.0);
+    }
+
+    #[test]
+    fn keeps_the_integral_term_when_ki_changes() {
+        let mut pid = pid(0.0, 1.0, 0.0, 10.0, 10.0);
+        for _ in 0..3 { pid.update(1.0, 0.1); }
+        assert_close(pid.get_integral_term(), 0.3);
+
+        pid.set_gain(Gain::Integral, 2.0).unwrap();
+        assert_close(pid.get_integral_term(), 0.3);
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        let config = PidConfig {
+            kp: 1.0, ki: 0.0, kd: 0.0, integral_limit: 1.0, output_limit: 1.0,
+        };
+
+        assert!(Pid::new(PidConfig { kp: f32::NAN, ..config }).is_err());
+        assert!(Pid::new(PidConfig { integral_limit: -1.0, ..config }).is_err());
+        assert!(Pid::new(PidConfig { output_limit: 0.0, ..config }).is_err());
+
+        let mut pid = Pid::new(config).unwrap();
+        assert!(pid.set_gain(Gain::Proportional, f32::INFINITY).is_err());
+        assert!(pid.set_limit(Limit::Output, -1.0).is_err());
+    }
+}
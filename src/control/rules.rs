@@ -0,0 +1,126 @@
+use crate::{
+    action::Action,
+    config::rules::{ RuleConfig, RuleValue },
+    hardware_model::{
+        Battery,
+        ComponentRegistry,
+        DepthSensor,
+        Dht11,
+        Ds18b20,
+        EchoSounder,
+        Imu,
+    },
+};
+
+/*
+ * Evaluates the configured rules once a tick. A rule without a reading
+ * keeps its state, so a sensor dropout doesn't fire it.
+ */
+pub struct RuleEngine {
+    rules: Vec<RuleConfig>,
+    active: Vec<bool>,
+    firings: u32,
+    last_fired: Option<usize>,
+}
+
+impl RuleEngine {
+    pub fn new(rules: &[RuleConfig]) -> Self {
+        Self {
+            rules: rules.to_vec(),
+            active: vec![false; rules.len()],
+            firings: 0,
+            last_fired: None,
+        }
+    }
+
+    // returns the actions of every rule that changed state
+    pub fn update(&mut self, components: &ComponentRegistry) -> Vec<Action> {
+        let mut actions = Vec::new();
+
+        for (i, rule) in self.rules.iter().enumerate() {
+            let when = &rule.when;
+            let Some(value) = read(when.value, when.component.as_deref(), components) else {
+                continue;
+            };
+
+            // an active rule is held until past its thresholds by the
+            // hysteresis
+            let margin = if self.active[i] { rule.hysteresis } else { 0.0 };
+            let met = when.above.map_or(true, |a| value > a - margin)
+                && when.below.map_or(true, |b| value < b + margin);
+
+            if met == self.active[i] { continue; }
+            self.active[i] = met;
+
+            if met {
+                println!("Rule {} fired: {:?} is {:.2}.", rule.name, when.value, value);
+                self.firings = self.firings.wrapping_add(1);
+                self.last_fired = Some(i);
+                actions.extend(rule.on_enter.iter().cloned());
+            } else {
+                println!("Rule {} cleared: {:?} is {:.2}.", rule.name, when.value, value);
+                actions.extend(rule.on_exit.iter().cloned());
+            }
+        }
+
+        actions
+    }
+
+    // one bit per rule, for the first 32
+    pub fn get_active(&self) -> u32 {
+        self.active.iter().take(32).enumerate()
+            .fold(0, |bits, (i, a)| if *a { bits | 1 << i } else { bits })
+    }
+
+    pub fn get_firings(&self) -> u32 {
+        self.firings
+    }
+
+    pub fn get_last_fired(&self) -> Option<usize> {
+        self.last_fired
+    }
+}
+
+fn read(value: RuleValue, component: Option<&str>, components: &ComponentRegistry)
+    -> Option<f32>
+{
+    let present = components.slots().iter()
+        .filter(|s| component.map_or(true, |name| s.name == name))
+        .filter_map(|s| s.component())
+        .filter(|c| c.is_enabled());
+
+    match value {
+        RuleValue::Humidity => present
+            .filter_map(|c| c.as_any().downcast_ref::<Dht11>())
+            .find(|d| d.is_last_read_valid())
+            .map(|d| d.get_humidity() as f32),
+        RuleValue::InternalTemperature => present
+            .filter_map(|c| c.as_any().downcast_ref::<Dht11>())
+            .find(|d| d.is_last_read_valid())
+            .map(|d| d.get_temperature() as f32),
+        RuleValue::WaterTemperature => present
+            .filter_map(|c| c.as_any().downcast_ref::<Ds18b20>())
+            .find_map(|p| p.get_temperature()),
+        RuleValue::Depth => present
+            .filter_map(|c| c.as_any().downcast_ref::<DepthSensor>())
+            .find_map(|d| d.get_depth()),
+        RuleValue::Altitude => present
+            .filter_map(|c| c.as_any().downcast_ref::<EchoSounder>())
+            .filter(|e| !e.is_stale())
+            .find_map(|e| e.get_range_m()),
+        RuleValue::Heading => present
+            .filter_map(|c| c.as_any().downcast_ref::<Imu>())
+            .find_map(|i| i.get_heading()),
+        RuleValue::Roll => present
+            .filter_map(|c| c.as_any().downcast_ref::<Imu>())
+            .find(|i| i.is_last_read_valid())
+            .map(|i| i.get_roll()),
+        RuleValue::Pitch => present
+            .filter_map(|c| c.as_any().downcast_ref::<Imu>())
+            .find(|i| i.is_last_read_valid())
+            .map(|i| i.get_pitch()),
+        RuleValue::BatteryVoltage => present
+            .filter_map(|c| c.as_any().downcast_ref::<Battery>())
+            .find_map(|b| b.get_voltage()),
+    }
+}
@@ -1,4 +1,23 @@
+/*
+ * Why a peripheral failed to come up, sent in module telemetry for
+ * optional components that were left out. 0x0 means no failure.
+ */
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum InitFailure {
+    // bad values in the config
+    Config = 0x1,
+    // a GPIO pin could not be claimed
+    Gpio = 0x2,
+    // an I2C, SPI, PWM or serial bus could not be opened
+    Bus = 0x3,
+    // the bus opened but the device did not answer or set up
+    Device = 0x4,
+    // the device is not on the system at all
+    NotFound = 0x5,
+}
+
 #[derive(Debug)]
 pub struct PeripheralInitError {
+    pub reason: InitFailure,
     pub message: String,
 }
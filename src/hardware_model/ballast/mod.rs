@@ -1,11 +1,18 @@
+mod pump;
+mod stepper;
+
 use {
     crate::{
+        command::{ BallastPositionCommand, Command },
         error::PeripheralInitError,
-        traits::Tick,
-        config::hardware::ballast::BallastConfig,
+        traits::{ Component, SubmarineComponent, Tick },
+        config::hardware::ballast::{ BallastConfig, BallastDriverConfig },
+        hardware_model::{ ComponentKind, debounced_input::DebouncedInput },
+        telemetry::{ BallastTelemetry, TELEMETRY_PACKET_SIZE },
     },
-    rppal::gpio::{ OutputPin, Gpio },
     common::commands::BallastCommand,
+    pump::PumpDriver,
+    stepper::StepperDriver,
 };
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -16,54 +23,82 @@ pub enum BallastState {
     Transition,
 }
 
+/*
+ * How the ballast tank is filled and emptied. The pump driver has no idea
+ * how full the tank is; drivers with position feedback also support
+ * absolute position targets.
+ */
+pub trait BallastDriver {
+    fn intake(&mut self);
+    fn discharge(&mut self);
+    fn stop(&mut self);
+
+    fn move_to(&mut self, _position: u32) -> Result<(), String> {
+        Err(String::from("driver has no position control"))
+    }
+
+    fn home(&mut self) -> Result<(), String> {
+        Err(String::from("driver has no home position"))
+    }
+
+    fn position(&self) -> Option<u32> {
+        None
+    }
+
+    fn is_homed(&self) -> bool {
+        false
+    }
+
+    // the furthest position a target may be set to
+    fn max_position(&self) -> Option<u32> {
+        None
+    }
+
+    // drivers that hold their position while idle release it when
+    // de-energized
+    fn set_energized(&mut self, _energized: bool) {}
+}
+
 pub struct Ballast {
-    discharge_mode_pin: OutputPin,
-    intake_mode_pin: OutputPin,
+    driver: Box<dyn BallastDriver>,
+    full_switch: Option<DebouncedInput>,
+    empty_switch: Option<DebouncedInput>,
+    position_target: Option<u32>,
     target_state: BallastState,
     state: BallastState,
+    enabled: bool,
+    inhibited: bool,
 }
 
 impl Ballast {
     pub fn new(config: &BallastConfig) -> Result<Self, PeripheralInitError> {
         Ok(Self {
-            discharge_mode_pin: Gpio::new().map_err(|e| {
-                PeripheralInitError{
-                    message: format!(
-                        "Failed to init Gpio for pin {}: {}",
-                        config.gpio.discharge_pin,
-                        e.to_string()
-                    )
-                }
-            })?.get(config.gpio.discharge_pin).map_err(|e| {
-                PeripheralInitError {
-                    message: format!(
-                        "Failed to get gpio pin {}: {}",
-                        config.gpio.discharge_pin,
-                        e.to_string()
-                    )
-                }
-            })?.into_output(),
-
-            intake_mode_pin: Gpio::new().map_err(|e| {
-                PeripheralInitError{
-                    message: format!(
-                        "Failed to init Gpio for pin {}: {}",
-                        config.gpio.intake_pin,
-                        e.to_string()
-                    )
-                }
-            })?.get(config.gpio.intake_pin).map_err(|e| {
-                PeripheralInitError {
-                    message: format!(
-                        "Failed to get gpio pin {}: {}",
-                        config.gpio.intake_pin,
-                        e.to_string()
-                    )
-                }
-            })?.into_output(),
+            driver: match &config.driver {
+                BallastDriverConfig::Pump(gpio) =>
+                    Box::new(PumpDriver::new(gpio)?),
+                BallastDriverConfig::Stepper(stepper) =>
+                    Box::new(StepperDriver::new(stepper)?),
+            },
+
+            full_switch: match &config.limit_switches {
+                Some(ls) => ls.full_pin.map(|pin| DebouncedInput::new(
+                    pin, ls.active_low, ls.debounce_ticks
+                )).transpose()?,
+                None => None,
+            },
+            empty_switch: match &config.limit_switches {
+                Some(ls) => ls.empty_pin.map(|pin| DebouncedInput::new(
+                    pin, ls.active_low, ls.debounce_ticks
+                )).transpose()?,
+                None => None,
+            },
 
+            position_target: None,
             state: BallastState::Idle,
             target_state: BallastState::Idle,
+            enabled: true,
+            // the first tick energizes the driver if actuation is allowed
+            inhibited: true,
         })
     }
 
@@ -71,6 +106,8 @@ impl Ballast {
         &mut self,
         cmd: &BallastCommand
     ) {
+        self.position_target = None;
+
         match cmd {
             // TODO: intake and discharge modes
             BallastCommand::Idle => self.set_idle_state(),
@@ -79,6 +116,61 @@ impl Ballast {
         }
     }
 
+    pub fn handle_position_command(&mut self, cmd: &BallastPositionCommand) {
+        match cmd {
+            BallastPositionCommand::SetPosition { position, .. } =>
+                self.set_position_target(*position),
+            BallastPositionCommand::Home { .. } => {
+                self.position_target = None;
+                self.set_idle_state();
+
+                if let Err(e) = self.driver.home() {
+                    eprintln!("Cannot home ballast: {}", e);
+                }
+            },
+        }
+    }
+
+    /*
+     * Fills or empties the tank until the driver reports the target
+     * position, then goes idle.
+     */
+    pub fn set_position_target(&mut self, position: u32) {
+        let Some(current) = self.driver.position() else {
+            eprintln!("Cannot set ballast position: position is unknown.");
+            return;
+        };
+
+        // a target past the end of travel would never be reached
+        if let Some(max) = self.driver.max_position().filter(|max| position > *max) {
+            eprintln!("Cannot set ballast position {}: travel ends at {}.", position, max);
+            return;
+        }
+
+        self.position_target = Some(position);
+
+        if position > current {
+            self.set_intake_state();
+        } else if position < current {
+            self.set_discharge_state();
+        } else {
+            self.position_target = None;
+            self.set_idle_state();
+        }
+    }
+
+    pub fn get_position(&self) -> Option<u32> {
+        self.driver.position()
+    }
+
+    pub fn get_position_target(&self) -> Option<u32> {
+        self.position_target
+    }
+
+    pub fn is_homed(&self) -> bool {
+        self.driver.is_homed()
+    }
+
     pub fn get_current_state(&self) -> BallastState {
         self.state
     }
@@ -86,6 +178,22 @@ impl Ballast {
     pub fn get_target_state(&self) -> BallastState {
         self.target_state
     }
+
+    pub fn has_full_switch(&self) -> bool {
+        self.full_switch.is_some()
+    }
+
+    pub fn has_empty_switch(&self) -> bool {
+        self.empty_switch.is_some()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.full_switch.as_ref().map_or(false, |s| s.is_active())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.empty_switch.as_ref().map_or(false, |s| s.is_active())
+    }
     
     fn set_discharge_state(&mut self) {
         self.state = BallastState::Transition;
@@ -101,30 +209,204 @@ impl Ballast {
     }
 
     fn stop_all(&mut self) {
-        self.discharge_mode_pin.set_low();
-        self.intake_mode_pin.set_low();
+        self.driver.stop();
+    }
+
+    // stop and go idle, so nothing resumes when the stop is lifted
+    fn halt(&mut self) {
+        self.stop_all();
+        self.position_target = None;
+        self.state = BallastState::Idle;
+        self.target_state = BallastState::Idle;
+    }
+
+    fn fill(&mut self) {
+        match self.position_target {
+            Some(position) => self.move_to(position),
+            None => self.driver.intake(),
+        }
+    }
+
+    fn empty(&mut self) {
+        match self.position_target {
+            Some(position) => self.move_to(position),
+            None => self.driver.discharge(),
+        }
+    }
+
+    fn move_to(&mut self, position: u32) {
+        if let Err(e) = self.driver.move_to(position) {
+            eprintln!("Failed to move ballast: {}", e);
+            self.position_target = None;
+            self.set_idle_state();
+            return;
+        }
+
+        if self.driver.position() == Some(position) {
+            self.position_target = None;
+            self.set_idle_state();
+        }
+    }
+
+    fn update_limit_switches(&mut self) {
+        if let Some(s) = self.full_switch.as_mut() { s.update(); }
+        if let Some(s) = self.empty_switch.as_mut() { s.update(); }
     }
 }
 
 impl Tick for Ballast {
     fn tick(&mut self, _tick_count: u32) {
+        self.update_limit_switches();
+
+        if self.inhibited {
+            self.stop_all();
+            return;
+        }
+
         if self.state == BallastState::Transition {
             self.stop_all();
         }
 
+        // the limit switches are hard stops, whatever state was commanded
         match self.state {
             BallastState::Idle => {
                 self.stop_all();
             },
+            BallastState::Intake if self.is_full() => {
+                self.stop_all();
+            },
             BallastState::Intake => {
-                self.intake_mode_pin.set_high();
-                self.discharge_mode_pin.set_low();
+                self.fill();
+            },
+            BallastState::Discharge if self.is_empty() => {
+                self.stop_all();
             },
             BallastState::Discharge => {
-                self.intake_mode_pin.set_low();
-                self.discharge_mode_pin.set_high();
+                self.empty();
             },
             BallastState::Transition => self.state = self.target_state.clone(),
         };
     }
 }
+
+impl SubmarineComponent for Ballast {
+    fn enable(&mut self) {
+        self.driver.set_energized(!self.inhibited);
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.halt();
+        self.driver.set_energized(false);
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for Ballast {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::Ballast
+    }
+
+    fn handle(&mut self, cmd: &Command) {
+        if self.inhibited {
+            eprintln!("Ballast is inhibited, ignoring command.");
+            return;
+        }
+
+        match cmd {
+            Command::Ballast(c) => self.handle_command(c.as_ref()),
+            Command::BallastPosition(c) => self.handle_position_command(c),
+            _ => {},
+        }
+    }
+
+    fn set_inhibited(&mut self, inhibited: bool) {
+        if inhibited == self.inhibited { return; }
+
+        if inhibited {
+            self.halt();
+        }
+        // only de-energize drivers holding position if the ballast is enabled
+        self.driver.set_energized(!inhibited && self.enabled);
+        self.inhibited = inhibited;
+    }
+
+    fn serialize_telemetry(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        let mut telemetry = BallastTelemetry::new();
+        telemetry.collect(self);
+        telemetry.serialize(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // moves one step towards its target on every move
+    struct FakeDriver {
+        position: u32,
+        max_position: u32,
+    }
+
+    impl BallastDriver for FakeDriver {
+        fn intake(&mut self) {}
+        fn discharge(&mut self) {}
+        fn stop(&mut self) {}
+
+        fn move_to(&mut self, position: u32) -> Result<(), String> {
+            if position > self.position { self.position += 1; }
+            if position < self.position { self.position -= 1; }
+            Ok(())
+        }
+
+        fn position(&self) -> Option<u32> {
+            Some(self.position)
+        }
+
+        fn max_position(&self) -> Option<u32> {
+            Some(self.max_position)
+        }
+    }
+
+    fn ballast(position: u32) -> Ballast {
+        Ballast {
+            driver: Box::new(FakeDriver { position, max_position: 10 }),
+            full_switch: None,
+            empty_switch: None,
+            position_target: None,
+            target_state: BallastState::Idle,
+            state: BallastState::Idle,
+            enabled: true,
+            inhibited: false,
+        }
+    }
+
+    #[test]
+    fn reaches_a_position_target_and_goes_idle() {
+        let mut ballast = ballast(2);
+        ballast.set_position_target(10);
+        assert_eq!(ballast.get_target_state(), BallastState::Intake);
+
+        for tick in 0..20 { ballast.tick(tick); }
+
+        assert_eq!(ballast.get_position(), Some(10));
+        assert_eq!(ballast.get_position_target(), None);
+        assert_eq!(ballast.get_current_state(), BallastState::Idle);
+    }
+
+    #[test]
+    fn rejects_targets_past_the_end_of_travel() {
+        let mut ballast = ballast(2);
+        ballast.set_position_target(11);
+
+        assert_eq!(ballast.get_position_target(), None);
+        assert_eq!(ballast.get_target_state(), BallastState::Idle);
+
+        for tick in 0..5 { ballast.tick(tick); }
+        assert_eq!(ballast.get_position(), Some(2));
+    }
+}
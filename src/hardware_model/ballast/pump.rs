@@ -0,0 +1,74 @@
+use rppal::gpio::{ OutputPin, Gpio };
+use crate::{
+    config::hardware::ballast::BallastGpioConfig,
+    error::{ InitFailure, PeripheralInitError },
+};
+use super::BallastDriver;
+
+pub struct PumpDriver {
+    discharge_mode_pin: OutputPin,
+    intake_mode_pin: OutputPin,
+}
+
+impl PumpDriver {
+    pub fn new(config: &BallastGpioConfig) -> Result<Self, PeripheralInitError> {
+        Ok(Self {
+            discharge_mode_pin: Gpio::new().map_err(|e| {
+                PeripheralInitError{
+                    reason: InitFailure::Gpio,
+                    message: format!(
+                        "Failed to init Gpio for pin {}: {}",
+                        config.discharge_pin,
+                        e.to_string()
+                    )
+                }
+            })?.get(config.discharge_pin).map_err(|e| {
+                PeripheralInitError {
+                    reason: InitFailure::Gpio,
+                    message: format!(
+                        "Failed to get gpio pin {}: {}",
+                        config.discharge_pin,
+                        e.to_string()
+                    )
+                }
+            })?.into_output(),
+
+            intake_mode_pin: Gpio::new().map_err(|e| {
+                PeripheralInitError{
+                    reason: InitFailure::Gpio,
+                    message: format!(
+                        "Failed to init Gpio for pin {}: {}",
+                        config.intake_pin,
+                        e.to_string()
+                    )
+                }
+            })?.get(config.intake_pin).map_err(|e| {
+                PeripheralInitError {
+                    reason: InitFailure::Gpio,
+                    message: format!(
+                        "Failed to get gpio pin {}: {}",
+                        config.intake_pin,
+                        e.to_string()
+                    )
+                }
+            })?.into_output(),
+        })
+    }
+}
+
+impl BallastDriver for PumpDriver {
+    fn intake(&mut self) {
+        self.intake_mode_pin.set_high();
+        self.discharge_mode_pin.set_low();
+    }
+
+    fn discharge(&mut self) {
+        self.intake_mode_pin.set_low();
+        self.discharge_mode_pin.set_high();
+    }
+
+    fn stop(&mut self) {
+        self.discharge_mode_pin.set_low();
+        self.intake_mode_pin.set_low();
+    }
+}
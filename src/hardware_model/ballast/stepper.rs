@@ -0,0 +1,334 @@
+use rppal::gpio::{ Gpio, InputPin, Level, OutputPin };
+use std::{
+    sync::{ Arc, Mutex },
+    thread,
+    time::Duration,
+};
+use crate::{
+    config::hardware::ballast::BallastStepperConfig,
+    error::{ InitFailure, PeripheralInitError },
+};
+use super::BallastDriver;
+
+const STEP_PULSE: Duration = Duration::from_micros(2);
+const IDLE_POLL: Duration = Duration::from_millis(1);
+// consecutive reads before the home switch counts as pressed
+const HOME_SWITCH_SAMPLES: u8 = 3;
+
+/*
+ * State shared between the controller and the step generation thread.
+ */
+#[derive(Debug)]
+struct StepperState {
+    target: i64,
+    position: i64,
+    homing: bool,
+    homed: bool,
+    energized: bool,
+    shutdown: bool,
+}
+
+struct StepperPins {
+    step: OutputPin,
+    dir: OutputPin,
+    enable: OutputPin,
+    home: InputPin,
+    home_active: Level,
+}
+
+struct Profile {
+    // homing gives up after this many steps without reaching the switch
+    max_homing_steps: u32,
+    max_speed: f32,
+    homing_speed: f32,
+    acceleration: f32,
+    invert_direction: bool,
+}
+
+/*
+ * Drives a syringe ballast through a step/dir/enable stepper driver. Steps
+ * are generated on a dedicated thread, since the tick rate is far too slow
+ * for them, with a trapezoidal speed profile. The position is only known
+ * after homing on the switch at the empty end, which happens the first
+ * time the driver is energized or on a home command.
+ */
+pub struct StepperDriver {
+    state: Arc<Mutex<StepperState>>,
+    max_position: u32,
+    home_on_energize: bool,
+    step_thread: Option<thread::JoinHandle<()>>,
+}
+
+impl StepperDriver {
+    pub fn new(config: &BallastStepperConfig) -> Result<Self, PeripheralInitError> {
+        if config.max_speed <= 0.0 || config.homing_speed <= 0.0
+            || config.acceleration <= 0.0 {
+
+            return Err(PeripheralInitError {
+                reason: InitFailure::Config,
+                message: String::from("Stepper speeds and acceleration must be positive"),
+            });
+        }
+
+        let home = get_pin(config.home_pin)?;
+        let (home, home_active) = if config.home_active_low {
+            (home.into_input_pullup(), Level::Low)
+        } else {
+            (home.into_input_pulldown(), Level::High)
+        };
+
+        let pins = StepperPins {
+            step: get_pin(config.step_pin)?.into_output_low(),
+            dir: get_pin(config.dir_pin)?.into_output_low(),
+            // A4988 and DRV8825 enable inputs are active low
+            enable: get_pin(config.enable_pin)?.into_output_high(),
+            home,
+            home_active,
+        };
+        let profile = Profile {
+            max_homing_steps: config.max_position.saturating_mul(2),
+            max_speed: config.max_speed,
+            homing_speed: config.homing_speed,
+            acceleration: config.acceleration,
+            invert_direction: config.invert_direction,
+        };
+
+        let state = Arc::new(Mutex::new(StepperState {
+            target: 0,
+            position: 0,
+            // nothing moves until the ballast is first uninhibited
+            homing: false,
+            homed: false,
+            energized: false,
+            shutdown: false,
+        }));
+        let thread_state = state.clone();
+
+        Ok(Self {
+            state,
+            max_position: config.max_position,
+            home_on_energize: true,
+            step_thread: Some(thread::spawn(move || {
+                generate_steps(thread_state, pins, profile);
+            })),
+        })
+    }
+
+    fn set_target(&mut self, target: i64) {
+        let mut state = self.state.lock().unwrap();
+
+        if state.homed {
+            state.target = target.clamp(0, self.max_position as i64);
+        }
+    }
+}
+
+impl BallastDriver for StepperDriver {
+    fn intake(&mut self) {
+        self.set_target(self.max_position as i64);
+    }
+
+    fn discharge(&mut self) {
+        self.set_target(0);
+    }
+
+    fn stop(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        state.target = state.position;
+    }
+
+    fn move_to(&mut self, position: u32) -> Result<(), String> {
+        if !self.is_homed() {
+            return Err(String::from("stepper is not homed"));
+        }
+
+        self.set_target(position as i64);
+        Ok(())
+    }
+
+    fn home(&mut self) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        state.homing = true;
+        state.homed = false;
+
+        Ok(())
+    }
+
+    fn position(&self) -> Option<u32> {
+        let state = self.state.lock().unwrap();
+
+        if state.homed { Some(state.position.max(0) as u32) } else { None }
+    }
+
+    fn is_homed(&self) -> bool {
+        self.state.lock().unwrap().homed
+    }
+
+    fn max_position(&self) -> Option<u32> {
+        Some(self.max_position)
+    }
+
+    fn set_energized(&mut self, energized: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.target = state.position;
+        state.energized = energized;
+
+        if !energized {
+            // an interrupted homing takes a new home command
+            state.homing = false;
+        } else if self.home_on_energize {
+            self.home_on_energize = false;
+            state.homing = !state.homed;
+        }
+    }
+}
+
+impl Drop for StepperDriver {
+    fn drop(&mut self) {
+        self.state.lock().unwrap().shutdown = true;
+
+        if let Some(handle) = self.step_thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn get_pin(pin: u8) -> Result<rppal::gpio::Pin, PeripheralInitError> {
+    Gpio::new().map_err(|e| {
+        PeripheralInitError{
+            reason: InitFailure::Gpio,
+            message: format!(
+                "Failed to init Gpio for pin {}: {}",
+                pin,
+                e.to_string()
+            )
+        }
+    })?.get(pin).map_err(|e| {
+        PeripheralInitError {
+            reason: InitFailure::Gpio,
+            message: format!(
+                "Failed to get gpio pin {}: {}",
+                pin,
+                e.to_string()
+            )
+        }
+    })
+}
+
+fn step(pins: &mut StepperPins, direction: i64, invert: bool) {
+    // positive direction fills the syringe
+    if (direction > 0) != invert {
+        pins.dir.set_high();
+    } else {
+        pins.dir.set_low();
+    }
+
+    pins.step.set_high();
+    thread::sleep(STEP_PULSE);
+    pins.step.set_low();
+}
+
+/*
+ * Runs until the driver is dropped. Speed is updated once per step using
+ * v' = sqrt(v^2 +/- 2a), which gives a constant acceleration over
+ * distance. The motor starts decelerating once the distance left is no
+ * more than its stopping distance, or straight away if the target moved
+ * behind it.
+ */
+fn generate_steps(
+    shared: Arc<Mutex<StepperState>>,
+    mut pins: StepperPins,
+    profile: Profile,
+) {
+    let min_speed = (2.0 * profile.acceleration).sqrt();
+    let mut speed: f32 = 0.0;
+    let mut direction: i64 = 0;
+    let mut home_samples: u8 = 0;
+    let mut homing_steps: u32 = 0;
+
+    loop {
+        let (target, position, homing, energized) = {
+            let state = shared.lock().unwrap();
+            if state.shutdown { break; }
+
+            (state.target, state.position, state.homing, state.energized)
+        };
+
+        if energized {
+            pins.enable.set_low();
+        } else {
+            pins.enable.set_high();
+            speed = 0.0;
+            direction = 0;
+            thread::sleep(IDLE_POLL);
+            continue;
+        }
+
+        if homing {
+            if pins.home.read() == pins.home_active {
+                home_samples += 1;
+            } else {
+                home_samples = 0;
+            }
+
+            if home_samples >= HOME_SWITCH_SAMPLES {
+                let mut state = shared.lock().unwrap();
+                state.position = 0;
+                state.target = 0;
+                state.homing = false;
+                state.homed = true;
+                home_samples = 0;
+                homing_steps = 0;
+                speed = 0.0;
+                direction = 0;
+                continue;
+            }
+
+            if homing_steps >= profile.max_homing_steps {
+                eprintln!("Ballast stepper failed to find its home switch.");
+                shared.lock().unwrap().homing = false;
+                homing_steps = 0;
+                continue;
+            }
+            homing_steps += 1;
+
+            step(&mut pins, -1, profile.invert_direction);
+            thread::sleep(Duration::from_secs_f32(1.0 / profile.homing_speed));
+            continue;
+        }
+
+        let distance = target - position;
+        if distance == 0 {
+            speed = 0.0;
+            direction = 0;
+            thread::sleep(IDLE_POLL);
+            continue;
+        }
+
+        let stopping_steps =
+            (speed * speed / (2.0 * profile.acceleration)) as i64;
+
+        if direction != 0
+            && (distance.signum() != direction || stopping_steps >= distance.abs()) {
+
+            speed = (speed * speed - 2.0 * profile.acceleration).max(0.0).sqrt();
+            if speed < min_speed {
+                // stopped, the next pass may start off in the other direction
+                speed = 0.0;
+                direction = 0;
+                continue;
+            }
+        } else {
+            direction = distance.signum();
+            speed = (speed * speed + 2.0 * profile.acceleration).sqrt()
+                .min(profile.max_speed);
+        }
+
+        step(&mut pins, direction, profile.invert_direction);
+        shared.lock().unwrap().position += direction;
+
+        thread::sleep(Duration::from_secs_f32(1.0 / speed).saturating_sub(STEP_PULSE));
+    }
+
+    pins.enable.set_high();
+}
@@ -0,0 +1,80 @@
+use crate::{
+    config::hardware::battery::BatteryConfig,
+    error::PeripheralInitError,
+    hardware_model::{ ina219::Ina219, ComponentKind },
+    traits::{ Component, SubmarineComponent, Tick },
+};
+
+/*
+ * Battery voltage and current draw, read every tick. There is no packet
+ * of its own; both go out with the envelope telemetry.
+ */
+pub struct Battery {
+    monitor: Ina219,
+    voltage: f32,
+    current: f32,
+    last_read_valid: bool,
+    enabled: bool,
+}
+
+impl Battery {
+    pub fn new(config: &BatteryConfig) -> Result<Self, PeripheralInitError> {
+        Ok(Self {
+            monitor: Ina219::new(config.bus, config.address, config.shunt_ohms)?,
+            voltage: 0.0,
+            current: 0.0,
+            last_read_valid: false,
+            enabled: true,
+        })
+    }
+
+    // None until there has been a good reading
+    pub fn get_voltage(&self) -> Option<f32> {
+        if self.last_read_valid { Some(self.voltage) } else { None }
+    }
+
+    // amps drawn, None until there has been a good reading
+    pub fn get_current(&self) -> Option<f32> {
+        if self.last_read_valid { Some(self.current) } else { None }
+    }
+
+    fn read(&mut self) -> Result<(), String> {
+        self.voltage = self.monitor.read_bus_voltage()?;
+        self.current = self.monitor.read_current()?;
+
+        Ok(())
+    }
+}
+
+impl Tick for Battery {
+    fn tick(&mut self, _tick_count: u32) {
+        match self.read() {
+            Ok(_) => self.last_read_valid = true,
+            Err(e) => {
+                eprintln!("Failed to read battery: {}", e);
+                self.last_read_valid = false;
+            }
+        }
+    }
+}
+
+impl SubmarineComponent for Battery {
+    fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.last_read_valid = false;
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for Battery {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::Battery
+    }
+}
@@ -0,0 +1,85 @@
+use rppal::gpio::{ Gpio, InputPin, Level };
+use crate::error::{ InitFailure, PeripheralInitError };
+
+/*
+ * A polled GPIO input, such as a switch. The debounced state only changes
+ * once the pin has read the new level for debounce_ticks samples in a row.
+ */
+pub struct DebouncedInput {
+    pin: InputPin,
+    active_level: Level,
+    debounce_ticks: u8,
+    active: bool,
+    pending_samples: u8,
+}
+
+impl DebouncedInput {
+    /*
+     * Active low inputs get the internal pull-up, active high inputs the
+     * internal pull-down.
+     */
+    pub fn new(
+        pin: u8,
+        active_low: bool,
+        debounce_ticks: u8,
+    ) -> Result<Self, PeripheralInitError> {
+        let pin = Gpio::new().map_err(|e| {
+            PeripheralInitError{
+                reason: InitFailure::Gpio,
+                message: format!(
+                    "Failed to init Gpio for pin {}: {}",
+                    pin,
+                    e.to_string()
+                )
+            }
+        })?.get(pin).map_err(|e| {
+            PeripheralInitError {
+                reason: InitFailure::Gpio,
+                message: format!(
+                    "Failed to get gpio pin {}: {}",
+                    pin,
+                    e.to_string()
+                )
+            }
+        })?;
+
+        let (pin, active_level) = if active_low {
+            (pin.into_input_pullup(), Level::Low)
+        } else {
+            (pin.into_input_pulldown(), Level::High)
+        };
+        let active = pin.read() == active_level;
+
+        Ok(Self {
+            pin,
+            active_level,
+            debounce_ticks,
+            active,
+            pending_samples: 0,
+        })
+    }
+
+    /*
+     * Samples the pin and returns the debounced state.
+     */
+    pub fn update(&mut self) -> bool {
+        let raw_active = self.pin.read() == self.active_level;
+
+        if raw_active == self.active {
+            self.pending_samples = 0;
+        } else {
+            self.pending_samples += 1;
+
+            if self.pending_samples >= self.debounce_ticks {
+                self.active = raw_active;
+                self.pending_samples = 0;
+            }
+        }
+
+        self.active
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
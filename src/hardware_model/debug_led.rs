@@ -0,0 +1,159 @@
+use rppal::gpio::{ OutputPin, Gpio };
+use crate::{
+    config::hardware::debug::DebugConfig,
+    error::{ InitFailure, PeripheralInitError },
+    hardware_model::ComponentKind,
+    traits::{ Component, SubmarineComponent, Tick },
+};
+
+// All durations are in ticks.
+const HEARTBEAT_PERIOD: u32 = 10;
+const HEARTBEAT_ON: u32 = 1;
+const BLINK_ON: u32 = 2;
+const BLINK_OFF: u32 = 3;
+const CODE_PAUSE: u32 = 15;
+
+/*
+ * Conditions the debug LED can signal. A status code is shown as a burst
+ * of short blinks, the number of blinks being the value of the code,
+ * followed by a long pause. While no condition is active the LED shows a
+ * single short heartbeat pulse every HEARTBEAT_PERIOD ticks.
+ */
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum StatusCode {
+    TelemetryReaderMissing = 2,
+    SensorFailure = 3,
+    CommandSocketError = 4,
+    TickOverrun = 5,
+}
+
+const STATUS_CODES: [StatusCode; 4] = [
+    StatusCode::TelemetryReaderMissing,
+    StatusCode::SensorFailure,
+    StatusCode::CommandSocketError,
+    StatusCode::TickOverrun,
+];
+
+pub struct DebugLed {
+    led_pin: OutputPin,
+    active_codes: [bool; STATUS_CODES.len()],
+    current_code: Option<StatusCode>,
+    sequence_tick: u32,
+    enabled: bool,
+}
+
+impl DebugLed {
+    pub fn new(config: &DebugConfig) -> Result<Self, PeripheralInitError> {
+        Ok(Self {
+            led_pin: Gpio::new().map_err(|e| {
+                PeripheralInitError{
+                    reason: InitFailure::Gpio,
+                    message: format!(
+                        "Failed to init Gpio for pin {}: {}",
+                        config.gpio.debug_led_pin,
+                        e.to_string()
+                    )
+                }
+            })?.get(config.gpio.debug_led_pin).map_err(|e| {
+                PeripheralInitError {
+                    reason: InitFailure::Gpio,
+                    message: format!(
+                        "Failed to get gpio pin {}: {}",
+                        config.gpio.debug_led_pin,
+                        e.to_string()
+                    )
+                }
+            })?.into_output(),
+
+            active_codes: [false; STATUS_CODES.len()],
+            current_code: None,
+            sequence_tick: 0,
+            enabled: true,
+        })
+    }
+
+    pub fn set_status(&mut self, code: StatusCode, active: bool) {
+        self.active_codes[Self::code_index(code)] = active;
+    }
+
+    pub fn is_status_active(&self, code: StatusCode) -> bool {
+        self.active_codes[Self::code_index(code)]
+    }
+
+    fn code_index(code: StatusCode) -> usize {
+        STATUS_CODES.iter().position(|c| *c == code).unwrap()
+    }
+
+    fn sequence_length(&self) -> u32 {
+        match self.current_code {
+            None => HEARTBEAT_PERIOD,
+            Some(code) => code as u32 * (BLINK_ON + BLINK_OFF) + CODE_PAUSE,
+        }
+    }
+
+    fn led_level(&self) -> bool {
+        match self.current_code {
+            None => self.sequence_tick < HEARTBEAT_ON,
+            Some(code) => {
+                let blink_period = BLINK_ON + BLINK_OFF;
+
+                self.sequence_tick < code as u32 * blink_period
+                    && self.sequence_tick % blink_period < BLINK_ON
+            }
+        }
+    }
+
+    /*
+     * Active codes are shown round robin, starting with the first active
+     * code after the one that was just shown.
+     */
+    fn next_code(&self) -> Option<StatusCode> {
+        let start = match self.current_code {
+            Some(code) => Self::code_index(code) + 1,
+            None => 0,
+        };
+
+        (0..STATUS_CODES.len())
+            .map(|i| STATUS_CODES[(start + i) % STATUS_CODES.len()])
+            .find(|code| self.is_status_active(*code))
+    }
+}
+
+impl Tick for DebugLed {
+    fn tick(&mut self, _tick_count: u32) {
+        if self.led_level() {
+            self.led_pin.set_high();
+        } else {
+            self.led_pin.set_low();
+        }
+
+        self.sequence_tick += 1;
+        if self.sequence_tick >= self.sequence_length() {
+            self.sequence_tick = 0;
+            self.current_code = self.next_code();
+        }
+    }
+}
+
+impl SubmarineComponent for DebugLed {
+    fn enable(&mut self) {
+        self.current_code = None;
+        self.sequence_tick = 0;
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.led_pin.set_low();
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for DebugLed {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::DebugLed
+    }
+}
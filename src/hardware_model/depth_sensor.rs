@@ -0,0 +1,253 @@
+// https://www.te.com/usa-en/product-CAT-BLPS0017.html (MS5837-30BA)
+
+use rppal::i2c::I2c;
+use crate::{
+    config::hardware::depth_sensor::DepthSensorConfig,
+    error::{ InitFailure, PeripheralInitError },
+    hardware_model::ComponentKind,
+    telemetry::{ DepthTelemetry, TELEMETRY_PACKET_SIZE },
+    traits::{ Component, SubmarineComponent, Tick },
+};
+
+const RESET: u8 = 0x1E;
+const PROM_READ: u8 = 0xA0;
+const ADC_READ: u8 = 0x00;
+// OSR 8192, at most 18ms a conversion
+const CONVERT_D1: u8 = 0x4A;
+const CONVERT_D2: u8 = 0x5A;
+
+const GRAVITY: f32 = 9.80665;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Conversion {
+    Idle,
+    Pressure,
+    Temperature(u32),
+}
+
+/*
+ * An MS5837 pressure sensor giving the depth. The pressure and
+ * temperature conversions each take a tick, so the sensor is never waited
+ * on and a new depth comes in every other tick.
+ */
+pub struct DepthSensor {
+    i2c: I2c,
+    // factory calibration, C1 to C6
+    calibration: [i64; 7],
+    fluid_density: f32,
+    surface_pressure_mbar: Option<f32>,
+    conversion: Conversion,
+    pressure_mbar: f32,
+    temperature_c: f32,
+    last_read_valid: bool,
+    enabled: bool,
+}
+
+impl DepthSensor {
+    pub fn new(config: &DepthSensorConfig) -> Result<Self, PeripheralInitError> {
+        let mut i2c = I2c::with_bus(config.bus).map_err(|e| PeripheralInitError {
+            reason: InitFailure::Bus,
+            message: format!("Failed to open I2C bus {}: {}", config.bus, e),
+        })?;
+        i2c.set_slave_address(config.address).map_err(|e| PeripheralInitError {
+            reason: InitFailure::Device,
+            message: format!("Failed to address MS5837 at {:#X}: {}", config.address, e),
+        })?;
+
+        let calibration = read_calibration(&mut i2c).map_err(|e| PeripheralInitError {
+            reason: InitFailure::Device,
+            message: format!("Failed to read MS5837 at {:#X}: {}", config.address, e),
+        })?;
+
+        Ok(Self {
+            i2c,
+            calibration,
+            fluid_density: config.fluid_density,
+            surface_pressure_mbar: config.surface_pressure_mbar,
+            conversion: Conversion::Idle,
+            pressure_mbar: 0.0,
+            temperature_c: 0.0,
+            last_read_valid: false,
+            enabled: true,
+        })
+    }
+
+    /*
+     * Meters below the surface, None until there has been a good reading.
+     */
+    pub fn get_depth(&self) -> Option<f32> {
+        if !self.last_read_valid { return None; }
+
+        let surface = self.surface_pressure_mbar?;
+        // 1 mbar is 100 Pa
+        Some((self.pressure_mbar - surface) * 100.0 / (self.fluid_density * GRAVITY))
+    }
+
+    pub fn get_pressure(&self) -> f32 {
+        self.pressure_mbar
+    }
+
+    pub fn get_temperature(&self) -> f32 {
+        self.temperature_c
+    }
+
+    pub fn is_last_read_valid(&self) -> bool {
+        self.last_read_valid
+    }
+
+    fn read_adc(&mut self) -> Result<u32, rppal::i2c::Error> {
+        let mut buffer = [0u8; 3];
+        self.i2c.write_read(&[ADC_READ], &mut buffer)?;
+
+        Ok(u32::from_be_bytes([0, buffer[0], buffer[1], buffer[2]]))
+    }
+
+    fn step_conversion(&mut self) -> Result<(), rppal::i2c::Error> {
+        self.conversion = match self.conversion {
+            Conversion::Idle => {
+                self.i2c.write(&[CONVERT_D1])?;
+                Conversion::Pressure
+            },
+            Conversion::Pressure => {
+                let d1 = self.read_adc()?;
+                self.i2c.write(&[CONVERT_D2])?;
+                Conversion::Temperature(d1)
+            },
+            Conversion::Temperature(d1) => {
+                let d2 = self.read_adc()?;
+                self.compensate(d1 as i64, d2 as i64);
+                self.i2c.write(&[CONVERT_D1])?;
+                Conversion::Pressure
+            },
+        };
+
+        Ok(())
+    }
+
+    /*
+     * First and second order compensation from the datasheet, in its
+     * integer arithmetic. Pressure comes out in 0.1 mbar and temperature
+     * in 0.01 C.
+     */
+    fn compensate(&mut self, d1: i64, d2: i64) {
+        let c = &self.calibration;
+
+        let dt = d2 - (c[5] << 8);
+        let temp = 2000 + ((dt * c[6]) >> 23);
+        let off = (c[2] << 16) + ((c[4] * dt) >> 7);
+        let sens = (c[1] << 15) + ((c[3] * dt) >> 8);
+
+        let (temp_i, off_i, sens_i) = if temp < 2000 {
+            let low = (temp - 2000) * (temp - 2000);
+            let (mut off_i, mut sens_i) = ((3 * low) >> 1, (5 * low) >> 3);
+
+            if temp < -1500 {
+                let very_low = (temp + 1500) * (temp + 1500);
+                off_i += 7 * very_low;
+                sens_i += 4 * very_low;
+            }
+
+            ((3 * dt * dt) >> 33, off_i, sens_i)
+        } else {
+            ((2 * dt * dt) >> 37, ((temp - 2000) * (temp - 2000)) >> 4, 0)
+        };
+
+        let off = off - off_i;
+        let sens = sens - sens_i;
+        let pressure = (((d1 * sens) >> 21) - off) >> 13;
+
+        self.temperature_c = (temp - temp_i) as f32 / 100.0;
+        self.pressure_mbar = pressure as f32 / 10.0;
+        self.last_read_valid = true;
+
+        if self.surface_pressure_mbar.is_none() {
+            println!("Surface pressure set to {:.1} mbar.", self.pressure_mbar);
+            self.surface_pressure_mbar = Some(self.pressure_mbar);
+        }
+    }
+}
+
+fn read_calibration(i2c: &mut I2c) -> Result<[i64; 7], String> {
+    i2c.write(&[RESET]).map_err(|e| e.to_string())?;
+    // the PROM is reloaded after a reset, which takes under 3ms
+    std::thread::sleep(std::time::Duration::from_millis(10));
+
+    let mut prom = [0u16; 7];
+    for (i, word) in prom.iter_mut().enumerate() {
+        let mut buffer = [0u8; 2];
+        i2c.write_read(&[PROM_READ + 2 * i as u8], &mut buffer)
+            .map_err(|e| e.to_string())?;
+        *word = u16::from_be_bytes(buffer);
+    }
+
+    let expected = (prom[0] >> 12) as u8;
+    if crc4(&prom) != expected {
+        return Err(String::from("calibration CRC mismatch"));
+    }
+
+    Ok(prom.map(|w| w as i64))
+}
+
+/*
+ * CRC4 of the PROM, the CRC bits of word 0 taken as zero. From TE
+ * application note AN520.
+ */
+fn crc4(prom: &[u16; 7]) -> u8 {
+    let mut words = [0u16; 8];
+    words[..7].copy_from_slice(prom);
+    words[0] &= 0x0FFF;
+
+    let mut remainder: u16 = 0;
+    for i in 0..16 {
+        let word = words[i >> 1];
+        remainder ^= if i % 2 == 1 { word & 0x00FF } else { word >> 8 };
+
+        for _ in 0..8 {
+            remainder = if remainder & 0x8000 != 0 {
+                (remainder << 1) ^ 0x3000
+            } else {
+                remainder << 1
+            };
+        }
+    }
+
+    ((remainder >> 12) & 0xF) as u8
+}
+
+impl Tick for DepthSensor {
+    fn tick(&mut self, _tick_count: u32) {
+        if let Err(e) = self.step_conversion() {
+            eprintln!("MS5837 Failure: {}", e);
+            self.last_read_valid = false;
+            self.conversion = Conversion::Idle;
+        }
+    }
+}
+
+impl SubmarineComponent for DepthSensor {
+    fn enable(&mut self) {
+        self.conversion = Conversion::Idle;
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.last_read_valid = false;
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for DepthSensor {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::DepthSensor
+    }
+
+    fn serialize_telemetry(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        let mut telemetry = DepthTelemetry::new();
+        telemetry.collect(self);
+        telemetry.serialize(buffer)
+    }
+}
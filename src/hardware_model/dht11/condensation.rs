@@ -0,0 +1,168 @@
+use std::collections::VecDeque;
+use crate::config::hardware::dht11::CondensationConfig;
+
+pub const CONDENSATION_LIKELY: u8 = 0x1;
+pub const HUMIDITY_RISING: u8 = 0x2;
+
+// Magnus formula coefficients, valid from -45C to 60C
+const MAGNUS_B: f32 = 17.62;
+const MAGNUS_C: f32 = 243.12;
+
+/*
+ * Tracks how close the hull air is to condensing. A steady rise in
+ * internal humidity is the earliest sign of a slow leak, so the rate of
+ * change is tracked over a rolling window of samples as well.
+ */
+#[derive(Debug)]
+pub struct CondensationMonitor {
+    spread_alarm_c: f32,
+    rate_alarm: f32,
+    window_len: usize,
+    sample_interval_s: f32,
+    humidity_window: VecDeque<f32>,
+    dew_point_c: f32,
+    humidity_rate: f32,
+    alarms: u8,
+}
+
+impl CondensationMonitor {
+    pub fn new(config: &CondensationConfig, sample_interval_s: u8) -> Self {
+        Self {
+            spread_alarm_c: config.dew_point_spread_alarm_c,
+            rate_alarm: config.humidity_rate_alarm,
+            window_len: (config.rate_window_samples as usize).max(2),
+            sample_interval_s: sample_interval_s as f32,
+            humidity_window: VecDeque::new(),
+            dew_point_c: 0.0,
+            humidity_rate: 0.0,
+            alarms: 0x0,
+        }
+    }
+
+    pub fn ingest(&mut self, temperature_c: f32, humidity_percent: f32) {
+        self.dew_point_c = dew_point(temperature_c, humidity_percent);
+
+        self.humidity_window.push_back(humidity_percent);
+        while self.humidity_window.len() > self.window_len {
+            self.humidity_window.pop_front();
+        }
+        self.humidity_rate = self.compute_humidity_rate();
+
+        let mut alarms = 0x0;
+        if temperature_c - self.dew_point_c <= self.spread_alarm_c {
+            alarms |= CONDENSATION_LIKELY;
+        }
+        if self.humidity_rate >= self.rate_alarm {
+            alarms |= HUMIDITY_RISING;
+        }
+
+        if alarms & !self.alarms & CONDENSATION_LIKELY != 0 {
+            eprintln!(
+                "Condensation likely: {:.1}C air, {:.1}C dew point",
+                temperature_c,
+                self.dew_point_c
+            );
+        }
+        if alarms & !self.alarms & HUMIDITY_RISING != 0 {
+            eprintln!(
+                "Internal humidity rising at {:.2}%/min",
+                self.humidity_rate
+            );
+        }
+
+        self.alarms = alarms;
+    }
+
+    pub fn get_dew_point(&self) -> f32 {
+        self.dew_point_c
+    }
+
+    // % relative humidity per minute
+    pub fn get_humidity_rate(&self) -> f32 {
+        self.humidity_rate
+    }
+
+    pub fn get_alarms(&self) -> u8 {
+        self.alarms
+    }
+
+    fn compute_humidity_rate(&self) -> f32 {
+        let samples = self.humidity_window.len();
+        if samples < 2 || self.sample_interval_s <= 0.0 { return 0.0; }
+
+        let span_s = (samples - 1) as f32 * self.sample_interval_s;
+        let delta = self.humidity_window[samples - 1] - self.humidity_window[0];
+
+        delta / span_s * 60.0
+    }
+}
+
+fn dew_point(temperature_c: f32, humidity_percent: f32) -> f32 {
+    // ln(0) is undefined, a reading of 0% is treated as bone dry instead
+    let rh = (humidity_percent / 100.0).clamp(0.01, 1.0);
+    let gamma = rh.ln() + MAGNUS_B * temperature_c / (MAGNUS_C + temperature_c);
+
+    MAGNUS_C * gamma / (MAGNUS_B - gamma)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor() -> CondensationMonitor {
+        CondensationMonitor::new(&CondensationConfig {
+            dew_point_spread_alarm_c: 3.0,
+            humidity_rate_alarm: 1.0,
+            rate_window_samples: 5,
+        }, 6)
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 0.05, "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn dew_point_matches_reference_values() {
+        assert_close(dew_point(20.0, 50.0), 9.26);
+        assert_close(dew_point(25.0, 60.0), 16.69);
+        assert_close(dew_point(10.0, 80.0), 6.71);
+        assert_close(dew_point(-5.0, 70.0), -9.63);
+        // saturated air is at its dew point
+        assert_close(dew_point(30.0, 100.0), 30.0);
+    }
+
+    #[test]
+    fn dry_readings_stay_finite() {
+        assert!(dew_point(25.0, 0.0).is_finite());
+    }
+
+    #[test]
+    fn alarms_when_close_to_condensing() {
+        let mut monitor = monitor();
+
+        monitor.ingest(20.0, 50.0);
+        assert_eq!(monitor.get_alarms() & CONDENSATION_LIKELY, 0);
+
+        // 20C at 85% condenses at 17.4C
+        monitor.ingest(20.0, 85.0);
+        assert_ne!(monitor.get_alarms() & CONDENSATION_LIKELY, 0);
+    }
+
+    #[test]
+    fn alarms_on_rising_humidity() {
+        let mut monitor = monitor();
+
+        // half a percent every 6s is 5% a minute
+        for i in 0..5 {
+            monitor.ingest(20.0, 40.0 + i as f32 * 0.5);
+        }
+        assert_close(monitor.get_humidity_rate(), 5.0);
+        assert_ne!(monitor.get_alarms() & HUMIDITY_RISING, 0);
+
+        for _ in 0..5 {
+            monitor.ingest(20.0, 42.0);
+        }
+        assert_close(monitor.get_humidity_rate(), 0.0);
+        assert_eq!(monitor.get_alarms() & HUMIDITY_RISING, 0);
+    }
+}
@@ -1,9 +1,14 @@
 // https://www.mouser.com/datasheet/2/758/DHT11-Technical-Data-Sheet-Translated-Version-1143054.pdf
 
+mod condensation;
+
+pub use condensation::CondensationMonitor;
 use crate::{
     config::hardware::dht11::Dht11Config,
-    error::PeripheralInitError,
-    traits::Tick,
+    error::{ InitFailure, PeripheralInitError },
+    hardware_model::ComponentKind,
+    telemetry::{ EnvironmentTelemetry, TELEMETRY_PACKET_SIZE },
+    traits::{ Component, SubmarineComponent, Tick },
 };
 use rppal::{
     gpio::{ IoPin, Mode, Gpio, Level },
@@ -20,6 +25,8 @@ pub struct Dht11 {
     last_measured_rh: (u8, u8),
     last_read_valid: bool,
     successive_failures: u8,
+    enabled: bool,
+    condensation: CondensationMonitor,
 }
 
 #[derive(Debug)]
@@ -34,6 +41,7 @@ impl Dht11 {
         Ok(Self {
             data_pin: Gpio::new().map_err(|e| {
                 PeripheralInitError{
+                    reason: InitFailure::Gpio,
                     message: format!(
                         "Failed to init Gpio for pin {}: {}",
                         config.gpio.data_pin,
@@ -42,6 +50,7 @@ impl Dht11 {
                 }
             })?.get(config.gpio.data_pin).map_err(|e| {
                 PeripheralInitError {
+                    reason: InitFailure::Gpio,
                     message: format!(
                         "Failed to get gpio pin {}: {}",
                         config.gpio.data_pin,
@@ -57,6 +66,11 @@ impl Dht11 {
             last_measured_rh: (0, 0),
             last_read_valid: false,
             successive_failures: 0,
+            enabled: true,
+            condensation: CondensationMonitor::new(
+                &config.condensation,
+                config.sample_interval
+            ),
         })
     }
 
@@ -72,6 +86,17 @@ impl Dht11 {
         self.last_read_valid
     }
 
+    pub fn get_condensation(&self) -> &CondensationMonitor {
+        &self.condensation
+    }
+
+    /*
+     * The second byte of each reading holds the decimal part.
+     */
+    fn reading_as_f32(reading: (u8, u8)) -> f32 {
+        reading.0 as f32 + reading.1 as f32 / 10.0
+    }
+
     fn read_sensor(&mut self) -> Result<(), Error> {
         self.send_start_signal()?;
         self.read_data_transmission()?;
@@ -175,6 +200,10 @@ impl Tick for Dht11 {
                 Ok(_) => {
                     self.last_read_valid = true;
                     self.successive_failures = 0;
+                    self.condensation.ingest(
+                        Self::reading_as_f32(self.last_measured_temp),
+                        Self::reading_as_f32(self.last_measured_rh),
+                    );
                 },
                 Err(e) => {
                     eprintln!("DHT11 Failure: {:#?}", e);
@@ -185,3 +214,31 @@ impl Tick for Dht11 {
         }
     }
 }
+
+impl SubmarineComponent for Dht11 {
+    fn enable(&mut self) {
+        self.successive_failures = 0;
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.last_read_valid = false;
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for Dht11 {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::Dht11
+    }
+
+    fn serialize_telemetry(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        let mut telemetry = EnvironmentTelemetry::new();
+        telemetry.collect(self);
+        telemetry.serialize(buffer)
+    }
+}
@@ -0,0 +1,345 @@
+// https://www.analog.com/media/en/technical-documentation/data-sheets/DS18B20.pdf
+
+use std::{
+    fmt,
+    fs,
+    path::{ Path, PathBuf },
+    sync::{ Arc, Mutex },
+    thread,
+    time::Duration,
+};
+use crate::{
+    config::hardware::ds18b20::Ds18b20Config,
+    error::{ InitFailure, PeripheralInitError },
+    hardware_model::ComponentKind,
+    telemetry::{ EnvironmentTelemetry, TELEMETRY_PACKET_SIZE },
+    traits::{ Component, SubmarineComponent, Tick },
+};
+
+// the probe reads this before its first conversion, it is never a real reading
+const POWER_ON_RESET_MILLIDEGREES: i32 = 85_000;
+const IDLE_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Default, Copy, Clone)]
+struct ProbeState {
+    temperature_c: Option<f32>,
+    successive_failures: u8,
+    crc_failures: u16,
+    sampling: bool,
+    shutdown: bool,
+}
+
+/*
+ * An external DS18B20 temperature probe read through the kernel's w1
+ * driver. Reading w1_slave starts a conversion that takes up to 750ms, so
+ * the probe is read on its own thread.
+ */
+pub struct Ds18b20 {
+    state: Arc<Mutex<ProbeState>>,
+    max_failures: u8,
+    sample_thread: Option<thread::JoinHandle<()>>,
+    enabled: bool,
+}
+
+impl Ds18b20 {
+    pub fn new(config: &Ds18b20Config) -> Result<Self, PeripheralInitError> {
+        let path = Path::new(&config.w1_root).join(&config.address).join("w1_slave");
+
+        if !path.exists() {
+            return Err(PeripheralInitError {
+                reason: InitFailure::NotFound,
+                message: format!(
+                    "DS18B20 {} not found at {}",
+                    config.name,
+                    path.display()
+                ),
+            });
+        }
+
+        let state = Arc::new(Mutex::new(ProbeState {
+            sampling: true,
+            ..Default::default()
+        }));
+        let thread_state = state.clone();
+        let interval = Duration::from_millis(config.sample_interval_ms as u64);
+
+        Ok(Self {
+            state,
+            max_failures: config.max_failures,
+            sample_thread: Some(thread::spawn(move || {
+                sample_probe(thread_state, path, interval);
+            })),
+            enabled: true,
+        })
+    }
+
+    /*
+     * None until the first good reading, or after max_failures failed
+     * reads in a row.
+     */
+    pub fn get_temperature(&self) -> Option<f32> {
+        let state = self.state.lock().unwrap();
+
+        if state.successive_failures >= self.max_failures {
+            None
+        } else {
+            state.temperature_c
+        }
+    }
+
+    pub fn get_crc_failures(&self) -> u16 {
+        self.state.lock().unwrap().crc_failures
+    }
+
+    pub fn is_reading_valid(&self) -> bool {
+        self.get_temperature().is_some()
+    }
+}
+
+impl Drop for Ds18b20 {
+    fn drop(&mut self) {
+        self.state.lock().unwrap().shutdown = true;
+
+        if let Some(handle) = self.sample_thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Error {
+    Read(String),
+    Crc,
+    Format,
+    PowerOnReset,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Read(e) => write!(f, "read failed: {}", e),
+            Error::Crc => write!(f, "CRC mismatch"),
+            Error::Format => write!(f, "malformed w1_slave"),
+            Error::PowerOnReset => write!(f, "no conversion since power on"),
+        }
+    }
+}
+
+/*
+ * w1_slave holds two lines, the scratchpad with the driver's CRC check
+ * and then the scratchpad again with the temperature in millidegrees:
+ *   72 01 4b 46 7f ff 0e 10 57 : crc=57 YES
+ *   72 01 4b 46 7f ff 0e 10 57 t=23125
+ */
+fn read_probe(path: &PathBuf) -> Result<f32, Error> {
+    let contents = fs::read_to_string(path).map_err(|e| Error::Read(e.to_string()))?;
+    let mut lines = contents.lines();
+
+    let crc_line = lines.next().ok_or(Error::Format)?;
+    if !crc_line.trim_end().ends_with("YES") {
+        return Err(Error::Crc);
+    }
+
+    let millidegrees: i32 = lines.next()
+        .and_then(|l| l.rsplit_once("t="))
+        .and_then(|(_, t)| t.trim().parse().ok())
+        .ok_or(Error::Format)?;
+
+    if millidegrees == POWER_ON_RESET_MILLIDEGREES {
+        return Err(Error::PowerOnReset);
+    }
+
+    Ok(millidegrees as f32 / 1000.0)
+}
+
+fn sample_probe(shared: Arc<Mutex<ProbeState>>, path: PathBuf, interval: Duration) {
+    loop {
+        let (sampling, shutdown) = {
+            let state = shared.lock().unwrap();
+            (state.sampling, state.shutdown)
+        };
+        if shutdown { break; }
+        if !sampling {
+            thread::sleep(IDLE_POLL);
+            continue;
+        }
+
+        let reading = read_probe(&path);
+        {
+            let mut state = shared.lock().unwrap();
+
+            match reading {
+                Ok(t) => {
+                    state.temperature_c = Some(t);
+                    state.successive_failures = 0;
+                },
+                Err(e) => {
+                    eprintln!("DS18B20 failure at {}: {}", path.display(), e);
+                    if let Error::Crc = e {
+                        state.crc_failures = state.crc_failures.saturating_add(1);
+                    }
+                    state.successive_failures = state.successive_failures.saturating_add(1);
+                },
+            }
+        }
+
+        // sleep in short steps so shutdown isn't held up by a long interval
+        let mut slept = Duration::ZERO;
+        while slept < interval && !shared.lock().unwrap().shutdown {
+            thread::sleep(IDLE_POLL);
+            slept += IDLE_POLL;
+        }
+    }
+}
+
+impl Tick for Ds18b20 {
+    fn tick(&mut self, _tick_count: u32) {}
+}
+
+impl SubmarineComponent for Ds18b20 {
+    fn enable(&mut self) {
+        self.state.lock().unwrap().sampling = true;
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        state.sampling = false;
+        state.temperature_c = None;
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for Ds18b20 {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::Ds18b20
+    }
+
+    fn serialize_telemetry(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        let mut telemetry = EnvironmentTelemetry::new();
+        telemetry.collect_water(self);
+        telemetry.serialize(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    const ADDRESS: &str = "28-0316a2795aff";
+
+    fn write_probe(root: &Path, contents: &str) -> PathBuf {
+        let dir = root.join(ADDRESS);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("w1_slave");
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn config(root: &Path) -> Ds18b20Config {
+        Ds18b20Config {
+            name: "water_temp".to_string(),
+            required: false,
+            address: ADDRESS.to_string(),
+            w1_root: root.to_string_lossy().into_owned(),
+            sample_interval_ms: 10,
+            max_failures: 2,
+        }
+    }
+
+    fn wait_for(probe: &Ds18b20, done: impl Fn(&Ds18b20) -> bool) {
+        let start = Instant::now();
+        while !done(probe) {
+            assert!(start.elapsed() < Duration::from_secs(2), "probe never settled");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn reads_temperature() {
+        let root = tempfile::tempdir().unwrap();
+        let path = write_probe(root.path(),
+            "72 01 4b 46 7f ff 0e 10 57 : crc=57 YES\n72 01 4b 46 7f ff 0e 10 57 t=23125\n");
+
+        assert_eq!(read_probe(&path).unwrap(), 23.125);
+    }
+
+    #[test]
+    fn reads_negative_temperature() {
+        let root = tempfile::tempdir().unwrap();
+        let path = write_probe(root.path(),
+            "5e ff 4b 46 7f ff 02 10 56 : crc=56 YES\n5e ff 4b 46 7f ff 02 10 56 t=-10125\n");
+
+        assert_eq!(read_probe(&path).unwrap(), -10.125);
+    }
+
+    #[test]
+    fn rejects_failed_crc() {
+        let root = tempfile::tempdir().unwrap();
+        let path = write_probe(root.path(),
+            "72 01 4b 46 7f ff 0e 10 57 : crc=12 NO\n72 01 4b 46 7f ff 0e 10 57 t=23125\n");
+
+        assert!(matches!(read_probe(&path), Err(Error::Crc)));
+    }
+
+    #[test]
+    fn rejects_power_on_reset() {
+        let root = tempfile::tempdir().unwrap();
+        let path = write_probe(root.path(),
+            "50 05 4b 46 7f ff 0c 10 1c : crc=1c YES\n50 05 4b 46 7f ff 0c 10 1c t=85000\n");
+
+        assert!(matches!(read_probe(&path), Err(Error::PowerOnReset)));
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        let root = tempfile::tempdir().unwrap();
+
+        let path = write_probe(root.path(), "");
+        assert!(matches!(read_probe(&path), Err(Error::Format)));
+
+        let path = write_probe(root.path(), "72 01 4b 46 7f ff 0e 10 57 : crc=57 YES\n");
+        assert!(matches!(read_probe(&path), Err(Error::Format)));
+
+        let path = write_probe(root.path(),
+            "72 01 4b 46 7f ff 0e 10 57 : crc=57 YES\n72 01 4b 46 7f ff 0e 10 57 t=warm\n");
+        assert!(matches!(read_probe(&path), Err(Error::Format)));
+
+        assert!(matches!(read_probe(&root.path().join("missing")), Err(Error::Read(_))));
+    }
+
+    #[test]
+    fn samples_a_fake_tree() {
+        let root = tempfile::tempdir().unwrap();
+        write_probe(root.path(),
+            "72 01 4b 46 7f ff 0e 10 57 : crc=57 YES\n72 01 4b 46 7f ff 0e 10 57 t=23125\n");
+
+        let probe = Ds18b20::new(&config(root.path())).unwrap();
+        wait_for(&probe, |p| p.get_temperature() == Some(23.125));
+
+        // sent as an environment packet carrying water readings only
+        let mut buffer = [0; TELEMETRY_PACKET_SIZE];
+        probe.serialize_telemetry(&mut buffer);
+        assert_eq!(buffer[12], 0x2);
+        assert_eq!(&buffer[13..17], &23.125f32.to_le_bytes());
+        assert_eq!(buffer[17], 1);
+
+        // the reading is dropped after max_failures bad reads in a row
+        write_probe(root.path(),
+            "72 01 4b 46 7f ff 0e 10 57 : crc=12 NO\n72 01 4b 46 7f ff 0e 10 57 t=23125\n");
+        wait_for(&probe, |p| !p.is_reading_valid());
+        assert!(probe.get_crc_failures() >= 2);
+    }
+
+    #[test]
+    fn missing_probe_fails_init() {
+        let root = tempfile::tempdir().unwrap();
+
+        assert!(Ds18b20::new(&config(root.path())).is_err());
+    }
+}
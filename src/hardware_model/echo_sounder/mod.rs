@@ -0,0 +1,237 @@
+mod ping_protocol;
+
+use ping_protocol::{ Distance, PingParser, DISTANCE_SIMPLE_ID };
+use rppal::uart::Uart;
+use crate::{
+    config::hardware::echo_sounder::EchoSounderConfig,
+    error::{ InitFailure, PeripheralInitError },
+    hardware_model::{ ComponentKind, serial_port },
+    telemetry::{ EchoSounderTelemetry, TELEMETRY_PACKET_SIZE },
+    traits::{ Component, SubmarineComponent, Tick },
+};
+
+/*
+ * A Ping style echo sounder looking down, giving the altitude above the
+ * bottom. The device is polled for a distance every poll interval, and the
+ * replies are picked up on later ticks as they arrive.
+ */
+pub struct EchoSounder {
+    uart: Uart,
+    parser: PingParser,
+    poll_interval_ticks: u8,
+    timeout_ticks: u8,
+    min_altitude_m: f32,
+    min_confidence: u8,
+    last_reading: Option<Distance>,
+    ticks_since_reading: u32,
+    low_altitude: bool,
+    enabled: bool,
+}
+
+impl EchoSounder {
+    pub fn new(config: &EchoSounderConfig) -> Result<Self, PeripheralInitError> {
+        if config.poll_interval_ticks == 0 {
+            return Err(PeripheralInitError {
+                reason: InitFailure::Config,
+                message: format!("Invalid poll interval for {}", config.name),
+            });
+        }
+
+        Ok(Self {
+            uart: serial_port::open(&config.serial)?,
+            parser: PingParser::new(),
+            poll_interval_ticks: config.poll_interval_ticks,
+            timeout_ticks: config.timeout_ticks,
+            min_altitude_m: config.min_altitude_m,
+            min_confidence: config.min_confidence,
+            last_reading: None,
+            ticks_since_reading: 0,
+            low_altitude: false,
+            enabled: true,
+        })
+    }
+
+    pub fn get_range_m(&self) -> Option<f32> {
+        self.last_reading.map(|r| r.distance_mm as f32 / 1000.0)
+    }
+
+    pub fn get_confidence(&self) -> u8 {
+        self.last_reading.map_or(0, |r| r.confidence)
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.last_reading.is_none()
+            || self.ticks_since_reading > self.timeout_ticks as u32
+    }
+
+    pub fn is_low_altitude(&self) -> bool {
+        self.low_altitude
+    }
+
+    pub fn get_checksum_failures(&self) -> u32 {
+        self.parser.get_checksum_failures()
+    }
+
+    fn read_replies(&mut self) -> Result<(), String> {
+        let parser = &mut self.parser;
+        let mut latest = None;
+
+        serial_port::drain(&mut self.uart, |bytes| {
+            for b in bytes {
+                if let Some(d) = parser.push(*b) { latest = Some(d); }
+            }
+        })?;
+
+        if latest.is_some() {
+            self.last_reading = latest;
+            self.ticks_since_reading = 0;
+        }
+
+        Ok(())
+    }
+
+    fn update_alarm(&mut self) {
+        let low = match self.last_reading {
+            Some(r) if !self.is_stale() && r.confidence >= self.min_confidence =>
+                (r.distance_mm as f32 / 1000.0) < self.min_altitude_m,
+            _ => false,
+        };
+
+        if low && !self.low_altitude {
+            eprintln!(
+                "Low altitude: {:.2}m above the bottom.",
+                self.get_range_m().unwrap_or(0.0)
+            );
+        }
+
+        self.low_altitude = low;
+    }
+}
+
+impl Tick for EchoSounder {
+    fn tick(&mut self, tick_count: u32) {
+        self.ticks_since_reading = self.ticks_since_reading.saturating_add(1);
+
+        if let Err(e) = self.read_replies() {
+            eprintln!("Failed to read echo sounder: {}", e);
+        }
+
+        if tick_count % self.poll_interval_ticks as u32 == 0 {
+            if let Err(e) = self.uart.write(&ping_protocol::request(DISTANCE_SIMPLE_ID)) {
+                eprintln!("Failed to poll echo sounder: {}", e);
+            }
+        }
+
+        self.update_alarm();
+    }
+}
+
+impl SubmarineComponent for EchoSounder {
+    fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.last_reading = None;
+        self.low_altitude = false;
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for EchoSounder {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::EchoSounder
+    }
+
+    fn serialize_telemetry(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        let mut telemetry = EchoSounderTelemetry::new();
+        telemetry.collect(self);
+        telemetry.serialize(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::hardware::serial::SerialConfig,
+        hardware_model::serial_port::fake::FakeDevice,
+    };
+    use ping_protocol::tests::distance_simple;
+
+    fn echo_sounder(device: &FakeDevice) -> EchoSounder {
+        EchoSounder::new(&EchoSounderConfig {
+            name: "echo sounder".to_string(),
+            required: false,
+            serial: SerialConfig {
+                device: device.config.device.clone(),
+                baud: device.config.baud,
+            },
+            poll_interval_ticks: 5,
+            timeout_ticks: 3,
+            min_altitude_m: 1.0,
+            min_confidence: 50,
+        }).unwrap()
+    }
+
+    #[test]
+    fn polls_for_a_distance() {
+        let mut device = FakeDevice::new();
+        let mut echo_sounder = echo_sounder(&device);
+
+        echo_sounder.tick(5);
+
+        let request = ping_protocol::request(DISTANCE_SIMPLE_ID);
+        assert_eq!(device.receive(request.len()), request);
+    }
+
+    #[test]
+    fn reads_the_range() {
+        let mut device = FakeDevice::new();
+        let mut echo_sounder = echo_sounder(&device);
+
+        device.send(&distance_simple(2_500, 90));
+        echo_sounder.tick(1);
+
+        assert_eq!(echo_sounder.get_range_m(), Some(2.5));
+        assert_eq!(echo_sounder.get_confidence(), 90);
+        assert!(!echo_sounder.is_stale());
+        assert!(!echo_sounder.is_low_altitude());
+
+        for tick in 2..6 { echo_sounder.tick(tick); }
+        assert!(echo_sounder.is_stale());
+    }
+
+    #[test]
+    fn alarms_only_on_confident_low_readings() {
+        let mut device = FakeDevice::new();
+        let mut echo_sounder = echo_sounder(&device);
+
+        device.send(&distance_simple(600, 20));
+        echo_sounder.tick(1);
+        assert!(!echo_sounder.is_low_altitude());
+
+        device.send(&distance_simple(600, 80));
+        echo_sounder.tick(2);
+        assert!(echo_sounder.is_low_altitude());
+    }
+
+    #[test]
+    fn counts_corrupt_frames() {
+        let mut device = FakeDevice::new();
+        let mut echo_sounder = echo_sounder(&device);
+
+        let mut corrupt = distance_simple(2_500, 90);
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 0xFF;
+        device.send(&corrupt);
+        echo_sounder.tick(1);
+
+        assert_eq!(echo_sounder.get_checksum_failures(), 1);
+        assert_eq!(echo_sounder.get_range_m(), None);
+    }
+}
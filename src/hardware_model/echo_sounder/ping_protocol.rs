@@ -0,0 +1,275 @@
+// https://docs.bluerobotics.com/ping-protocol/
+
+/*
+ * Every message is framed as:
+ *   'B' 'R' | payload length u16 | message id u16 | src id u8 | dst id u8
+ *   | payload | checksum u16
+ * with little endian fields. The checksum is the sum of every byte before
+ * it.
+ */
+const START_1: u8 = b'B';
+const START_2: u8 = b'R';
+const HEADER_SIZE: usize = 8;
+const CHECKSUM_SIZE: usize = 2;
+// larger than any message we ask for, anything longer is line noise
+const MAX_PAYLOAD_SIZE: usize = 64;
+
+const GENERAL_REQUEST_ID: u16 = 6;
+pub const DISTANCE_ID: u16 = 1211;
+pub const DISTANCE_SIMPLE_ID: u16 = 1212;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Distance {
+    pub distance_mm: u32,
+    pub confidence: u8,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum State {
+    Start1,
+    Start2,
+    Header,
+    Payload,
+    Checksum,
+}
+
+#[derive(Debug)]
+pub struct PingParser {
+    state: State,
+    buffer: Vec<u8>,
+    payload_length: usize,
+    checksum_failures: u32,
+}
+
+impl PingParser {
+    pub fn new() -> Self {
+        Self {
+            state: State::Start1,
+            buffer: Vec::with_capacity(HEADER_SIZE + MAX_PAYLOAD_SIZE + CHECKSUM_SIZE),
+            payload_length: 0,
+            checksum_failures: 0,
+        }
+    }
+
+    pub fn get_checksum_failures(&self) -> u32 {
+        self.checksum_failures
+    }
+
+    /*
+     * Feeds one byte to the parser, returning the distance once a distance
+     * message is complete. Other messages are ignored.
+     */
+    pub fn push(&mut self, byte: u8) -> Option<Distance> {
+        match self.state {
+            State::Start1 => {
+                if byte == START_1 {
+                    self.buffer.clear();
+                    self.buffer.push(byte);
+                    self.state = State::Start2;
+                }
+            },
+            State::Start2 => {
+                if byte == START_2 {
+                    self.buffer.push(byte);
+                    self.state = State::Header;
+                } else if byte != START_1 {
+                    // a repeated 'B' may still start the frame
+                    self.state = State::Start1;
+                }
+            },
+            State::Header => {
+                self.buffer.push(byte);
+
+                if self.buffer.len() == HEADER_SIZE {
+                    self.payload_length =
+                        u16::from_le_bytes([self.buffer[2], self.buffer[3]]) as usize;
+
+                    self.state = if self.payload_length > MAX_PAYLOAD_SIZE {
+                        State::Start1
+                    } else if self.payload_length == 0 {
+                        State::Checksum
+                    } else {
+                        State::Payload
+                    };
+                }
+            },
+            State::Payload => {
+                self.buffer.push(byte);
+
+                if self.buffer.len() == HEADER_SIZE + self.payload_length {
+                    self.state = State::Checksum;
+                }
+            },
+            State::Checksum => {
+                self.buffer.push(byte);
+
+                if self.buffer.len() == HEADER_SIZE + self.payload_length + CHECKSUM_SIZE {
+                    self.state = State::Start1;
+                    return self.finish_message();
+                }
+            },
+        }
+
+        None
+    }
+
+    fn finish_message(&mut self) -> Option<Distance> {
+        let (message, checksum) = self.buffer.split_at(self.buffer.len() - CHECKSUM_SIZE);
+
+        if checksum_of(message) != u16::from_le_bytes([checksum[0], checksum[1]]) {
+            self.checksum_failures = self.checksum_failures.wrapping_add(1);
+            return None;
+        }
+
+        let id = u16::from_le_bytes([message[4], message[5]]);
+        let payload = &message[HEADER_SIZE..];
+
+        match id {
+            DISTANCE_SIMPLE_ID if payload.len() >= 5 => Some(Distance {
+                distance_mm: u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]),
+                confidence: payload[4],
+            }),
+            // same reading with the confidence widened to u16
+            DISTANCE_ID if payload.len() >= 6 => Some(Distance {
+                distance_mm: u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]),
+                confidence: u16::from_le_bytes([payload[4], payload[5]]).min(100) as u8,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/*
+ * Builds a general_request asking the device to send the given message.
+ */
+pub fn request(message_id: u16) -> Vec<u8> {
+    let mut message = vec![START_1, START_2];
+    message.extend_from_slice(&2u16.to_le_bytes());
+    message.extend_from_slice(&GENERAL_REQUEST_ID.to_le_bytes());
+    // source and destination device IDs, 0 is the host and the default device
+    message.extend_from_slice(&[0, 0]);
+    message.extend_from_slice(&message_id.to_le_bytes());
+
+    let checksum = checksum_of(&message);
+    message.extend_from_slice(&checksum.to_le_bytes());
+
+    message
+}
+
+fn checksum_of(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |sum, b| sum.wrapping_add(*b as u16))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn frame(id: u16, payload: &[u8]) -> Vec<u8> {
+        let mut message = vec![START_1, START_2];
+        message.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        message.extend_from_slice(&id.to_le_bytes());
+        message.extend_from_slice(&[1, 0]);
+        message.extend_from_slice(payload);
+
+        let checksum = checksum_of(&message);
+        message.extend_from_slice(&checksum.to_le_bytes());
+        message
+    }
+
+    pub fn distance_simple(distance_mm: u32, confidence: u8) -> Vec<u8> {
+        let mut payload = distance_mm.to_le_bytes().to_vec();
+        payload.push(confidence);
+        frame(DISTANCE_SIMPLE_ID, &payload)
+    }
+
+    fn feed(parser: &mut PingParser, bytes: &[u8]) -> Vec<Distance> {
+        bytes.iter().filter_map(|b| parser.push(*b)).collect()
+    }
+
+    #[test]
+    fn parses_distance_simple() {
+        let mut parser = PingParser::new();
+
+        assert_eq!(
+            feed(&mut parser, &distance_simple(2500, 87)),
+            vec![Distance { distance_mm: 2500, confidence: 87 }]
+        );
+    }
+
+    #[test]
+    fn parses_distance_with_wide_confidence() {
+        let mut parser = PingParser::new();
+        let mut payload = 1200u32.to_le_bytes().to_vec();
+        payload.extend_from_slice(&250u16.to_le_bytes());
+        // the rest of the distance message is not read
+        payload.extend_from_slice(&[0; 14]);
+
+        assert_eq!(
+            feed(&mut parser, &frame(DISTANCE_ID, &payload)),
+            vec![Distance { distance_mm: 1200, confidence: 100 }]
+        );
+    }
+
+    #[test]
+    fn parses_frames_split_across_reads() {
+        let mut parser = PingParser::new();
+        let message = distance_simple(900, 50);
+        let (first, rest) = message.split_at(5);
+
+        assert!(feed(&mut parser, first).is_empty());
+        assert_eq!(feed(&mut parser, rest).len(), 1);
+    }
+
+    #[test]
+    fn parses_back_to_back_frames() {
+        let mut parser = PingParser::new();
+        let mut bytes = distance_simple(1000, 10);
+        bytes.extend(distance_simple(2000, 20));
+
+        let distances = feed(&mut parser, &bytes);
+        assert_eq!(distances.len(), 2);
+        assert_eq!(distances[1].distance_mm, 2000);
+    }
+
+    #[test]
+    fn drops_corrupt_frames_and_recovers() {
+        let mut parser = PingParser::new();
+        let mut corrupt = distance_simple(1000, 10);
+        corrupt[9] ^= 0xFF;
+
+        assert!(feed(&mut parser, &corrupt).is_empty());
+        assert_eq!(parser.get_checksum_failures(), 1);
+
+        assert_eq!(feed(&mut parser, &distance_simple(3000, 30)).len(), 1);
+    }
+
+    #[test]
+    fn skips_noise_before_a_frame() {
+        let mut parser = PingParser::new();
+        let mut bytes = b"\x00\xffBxB".to_vec();
+        bytes.extend(distance_simple(700, 70));
+
+        assert_eq!(
+            feed(&mut parser, &bytes),
+            vec![Distance { distance_mm: 700, confidence: 70 }]
+        );
+        assert_eq!(parser.get_checksum_failures(), 0);
+    }
+
+    #[test]
+    fn drops_oversized_payloads() {
+        let mut parser = PingParser::new();
+        let mut bytes = frame(DISTANCE_SIMPLE_ID, &[0; MAX_PAYLOAD_SIZE + 1])[..HEADER_SIZE].to_vec();
+        bytes.extend(distance_simple(400, 40));
+
+        assert_eq!(feed(&mut parser, &bytes).len(), 1);
+    }
+
+    #[test]
+    fn ignores_other_messages() {
+        let mut parser = PingParser::new();
+
+        assert!(feed(&mut parser, &request(DISTANCE_SIMPLE_ID)).is_empty());
+        assert_eq!(parser.get_checksum_failures(), 0);
+    }
+}
@@ -0,0 +1,287 @@
+mod nmea;
+
+use nmea::{ Position, Sentence };
+use rppal::uart::Uart;
+use std::time::Duration;
+use crate::{
+    clock,
+    config::hardware::gps::GpsConfig,
+    error::PeripheralInitError,
+    hardware_model::{ ComponentKind, serial_port },
+    telemetry::{ GpsTelemetry, TELEMETRY_PACKET_SIZE },
+    traits::{ Component, SubmarineComponent, Tick },
+};
+
+// NMEA sentences are at most 82 characters
+const MAX_SENTENCE_LENGTH: usize = 82;
+
+/*
+ * The last position fix, kept after the fix is lost so the vehicle knows
+ * where it was when it dived.
+ */
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Fix {
+    pub position: Position,
+    // seconds since the unix epoch, if the date was known
+    pub unix_time: Option<u32>,
+    pub quality: u8,
+    pub satellites: u8,
+}
+
+/*
+ * A serial GPS receiver, only useful at the surface. Valid fixes also set
+ * the controller's wall clock.
+ */
+pub struct Gps {
+    uart: Uart,
+    line: Vec<u8>,
+    timeout_ticks: u8,
+    set_system_clock: bool,
+    system_clock_set: bool,
+    last_fix: Option<Fix>,
+    // days since the epoch from the last RMC sentence, to date GGA times
+    date_days: Option<u32>,
+    quality: u8,
+    satellites: u8,
+    ticks_since_fix: u32,
+    parse_failures: u32,
+    enabled: bool,
+}
+
+impl Gps {
+    pub fn new(config: &GpsConfig) -> Result<Self, PeripheralInitError> {
+        Ok(Self {
+            uart: serial_port::open(&config.serial)?,
+            line: Vec::with_capacity(MAX_SENTENCE_LENGTH),
+            timeout_ticks: config.timeout_ticks,
+            set_system_clock: config.set_system_clock,
+            system_clock_set: false,
+            last_fix: None,
+            date_days: None,
+            quality: 0,
+            satellites: 0,
+            ticks_since_fix: u32::MAX,
+            parse_failures: 0,
+            enabled: true,
+        })
+    }
+
+    pub fn get_last_fix(&self) -> Option<Fix> {
+        self.last_fix
+    }
+
+    pub fn has_fix(&self) -> bool {
+        self.last_fix.is_some() && self.ticks_since_fix <= self.timeout_ticks as u32
+    }
+
+    pub fn get_quality(&self) -> u8 {
+        self.quality
+    }
+
+    pub fn get_satellites(&self) -> u8 {
+        self.satellites
+    }
+
+    pub fn get_parse_failures(&self) -> u32 {
+        self.parse_failures
+    }
+
+    fn read_sentences(&mut self) -> Result<(), String> {
+        let mut lines = Vec::new();
+        let line = &mut self.line;
+
+        serial_port::drain(&mut self.uart, |bytes| {
+            for b in bytes {
+                match b {
+                    b'\n' => lines.push(std::mem::take(line)),
+                    b'\r' => {},
+                    // a line this long has lost its end, start over
+                    _ if line.len() >= MAX_SENTENCE_LENGTH => line.clear(),
+                    _ => line.push(*b),
+                }
+            }
+        })?;
+
+        for l in lines {
+            self.handle_line(&String::from_utf8_lossy(&l));
+        }
+
+        Ok(())
+    }
+
+    fn handle_line(&mut self, line: &str) {
+        if line.is_empty() { return; }
+
+        match nmea::parse(line) {
+            Ok(Some(Sentence::Gga(gga))) => {
+                self.quality = gga.quality;
+                self.satellites = gga.satellites;
+
+                if let (Some(position), true) = (gga.position, gga.quality > 0) {
+                    let unix_time = gga.time_of_day
+                        .zip(self.date_days)
+                        .map(|(time, days)| days * 86_400 + time);
+                    self.update_fix(position, unix_time);
+                }
+            },
+            Ok(Some(Sentence::Rmc(rmc))) => {
+                if let Some(t) = rmc.unix_time {
+                    self.date_days = Some(t / 86_400);
+                }
+
+                if !rmc.valid { return; }
+                if let Some(t) = rmc.unix_time { self.set_clock(t); }
+                if let Some(position) = rmc.position {
+                    self.update_fix(position, rmc.unix_time);
+                }
+            },
+            Ok(None) => {},
+            Err(e) => {
+                self.parse_failures = self.parse_failures.wrapping_add(1);
+                eprintln!("Bad NMEA sentence: {}", e);
+            },
+        }
+    }
+
+    fn update_fix(&mut self, position: Position, unix_time: Option<u32>) {
+        self.last_fix = Some(Fix {
+            position,
+            unix_time,
+            quality: self.quality,
+            satellites: self.satellites,
+        });
+        self.ticks_since_fix = 0;
+    }
+
+    fn set_clock(&mut self, unix_time: u32) {
+        let since_epoch = Duration::from_secs(unix_time as u64);
+        clock::set_wall_clock(since_epoch);
+
+        if self.set_system_clock && !self.system_clock_set {
+            match clock::set_system_clock(since_epoch) {
+                Ok(_) => println!("System clock set from GPS."),
+                Err(e) => eprintln!("Failed to set system clock: {}", e),
+            }
+            // only try once, a failure here won't go away by itself
+            self.system_clock_set = true;
+        }
+    }
+}
+
+impl Tick for Gps {
+    fn tick(&mut self, _tick_count: u32) {
+        self.ticks_since_fix = self.ticks_since_fix.saturating_add(1);
+
+        if let Err(e) = self.read_sentences() {
+            eprintln!("Failed to read GPS: {}", e);
+        }
+    }
+}
+
+impl SubmarineComponent for Gps {
+    fn enable(&mut self) {
+        self.line.clear();
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for Gps {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::Gps
+    }
+
+    fn serialize_telemetry(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        let mut telemetry = GpsTelemetry::new();
+        telemetry.collect(self);
+        telemetry.serialize(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::hardware::serial::SerialConfig,
+        hardware_model::serial_port::fake::FakeDevice,
+    };
+
+    fn gps(device: &FakeDevice) -> Gps {
+        Gps::new(&GpsConfig {
+            name: "gps".to_string(),
+            required: false,
+            serial: SerialConfig {
+                device: device.config.device.clone(),
+                baud: device.config.baud,
+            },
+            timeout_ticks: 2,
+            set_system_clock: false,
+        }).unwrap()
+    }
+
+    #[test]
+    fn takes_a_fix_from_recorded_nmea() {
+        let mut device = FakeDevice::new();
+        let mut gps = gps(&device);
+
+        device.send(b"$GNRMC,123519,A,4807.038,N,01131.000,W,022.4,084.4,230324,003.1,W*6D\r\n");
+        device.send(b"$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47\r\n");
+        gps.tick(0);
+
+        let fix = gps.get_last_fix().unwrap();
+        assert!(gps.has_fix());
+        assert_eq!(fix.quality, 1);
+        assert_eq!(fix.satellites, 8);
+        // the GGA time is dated from the RMC before it
+        assert_eq!(fix.unix_time, Some(1_711_197_319));
+        assert!((fix.position.longitude - (11.0 + 31.0 / 60.0)).abs() < 1e-6);
+        assert_eq!(clock::wall_clock().map(|t| t.as_secs() >= 1_711_197_319), Some(true));
+    }
+
+    #[test]
+    fn joins_sentences_split_across_reads() {
+        let mut device = FakeDevice::new();
+        let mut gps = gps(&device);
+
+        device.send(b"$GPGGA,123519,4807.038,N,0113");
+        gps.tick(0);
+        assert_eq!(gps.get_last_fix(), None);
+
+        device.send(b"1.000,E,1,08,0.9,545.4,M,46.9,M,,*47\r\n");
+        gps.tick(0);
+        assert!(gps.get_last_fix().is_some());
+    }
+
+    #[test]
+    fn keeps_the_last_fix_once_lost() {
+        let mut device = FakeDevice::new();
+        let mut gps = gps(&device);
+
+        device.send(b"$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47\r\n");
+        gps.tick(0);
+        for _ in 0..3 { gps.tick(0); }
+
+        assert!(!gps.has_fix());
+        assert!(gps.get_last_fix().is_some());
+    }
+
+    #[test]
+    fn counts_corrupt_sentences() {
+        let mut device = FakeDevice::new();
+        let mut gps = gps(&device);
+
+        device.send(b"$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*48\r\n");
+        device.send(b"$GPGGA,123519,4807.038,N,01131.000,E,1,08\r\n");
+        gps.tick(0);
+
+        assert_eq!(gps.get_parse_failures(), 2);
+        assert_eq!(gps.get_last_fix(), None);
+    }
+}
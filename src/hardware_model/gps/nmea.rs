@@ -0,0 +1,238 @@
+// https://gpsd.gitlab.io/gpsd/NMEA.html
+
+/*
+ * Only the sentences needed for a fix and the time are understood:
+ *   GGA: time, position, fix quality and satellites in use
+ *   RMC: time, date, position and whether the fix is valid
+ * Any talker (GP, GN, GL...) is accepted.
+ */
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Position {
+    // degrees, north and east positive
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Gga {
+    // seconds since midnight UTC
+    pub time_of_day: Option<u32>,
+    pub position: Option<Position>,
+    // 0 is no fix, 1 GPS, 2 DGPS, others are rarer fix types
+    pub quality: u8,
+    pub satellites: u8,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Rmc {
+    pub unix_time: Option<u32>,
+    pub position: Option<Position>,
+    pub valid: bool,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Sentence {
+    Gga(Gga),
+    Rmc(Rmc),
+}
+
+/*
+ * Parses one line, without its line ending. Sentences of other types are
+ * Ok(None).
+ */
+pub fn parse(line: &str) -> Result<Option<Sentence>, String> {
+    let body = line.strip_prefix('$').ok_or("missing $")?;
+    let (body, checksum) = body.rsplit_once('*').ok_or("missing checksum")?;
+
+    let expected = u8::from_str_radix(checksum.trim(), 16)
+        .map_err(|_| format!("bad checksum field {}", checksum))?;
+    let actual = body.bytes().fold(0u8, |sum, b| sum ^ b);
+    if expected != actual {
+        return Err(format!("checksum {:#X} does not match {:#X}", actual, expected));
+    }
+
+    let fields: Vec<&str> = body.split(',').collect();
+    let address = fields[0];
+    if address.len() < 3 { return Err(format!("bad address {}", address)); }
+
+    // line noise can pass the checksum, so the address may not be ASCII
+    if address.ends_with("GGA") {
+        parse_gga(&fields).map(|s| Some(Sentence::Gga(s)))
+    } else if address.ends_with("RMC") {
+        parse_rmc(&fields).map(|s| Some(Sentence::Rmc(s)))
+    } else {
+        Ok(None)
+    }
+}
+
+fn parse_gga(fields: &[&str]) -> Result<Gga, String> {
+    if fields.len() < 8 { return Err(String::from("short GGA sentence")); }
+
+    Ok(Gga {
+        time_of_day: parse_time_of_day(fields[1]),
+        position: parse_position(fields[2], fields[3], fields[4], fields[5]),
+        quality: fields[6].parse().unwrap_or(0),
+        satellites: fields[7].parse().unwrap_or(0),
+    })
+}
+
+fn parse_rmc(fields: &[&str]) -> Result<Rmc, String> {
+    if fields.len() < 10 { return Err(String::from("short RMC sentence")); }
+
+    let unix_time = parse_time_of_day(fields[1])
+        .zip(parse_date(fields[9]))
+        .map(|(time, days)| days * 86_400 + time);
+
+    Ok(Rmc {
+        unix_time,
+        position: parse_position(fields[3], fields[4], fields[5], fields[6]),
+        valid: fields[2] == "A",
+    })
+}
+
+// hhmmss.ss, fractions of a second are dropped
+fn parse_time_of_day(field: &str) -> Option<u32> {
+    if field.len() < 6 { return None; }
+
+    let hours: u32 = field.get(0..2)?.parse().ok()?;
+    let minutes: u32 = field.get(2..4)?.parse().ok()?;
+    let seconds: u32 = field.get(4..6)?.parse().ok()?;
+
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
+
+// ddmmyy, as days since the unix epoch
+fn parse_date(field: &str) -> Option<u32> {
+    if field.len() != 6 { return None; }
+
+    let day: u32 = field.get(0..2)?.parse().ok()?;
+    let month: u32 = field.get(2..4)?.parse().ok()?;
+    let year: u32 = 2000 + field.get(4..6)?.parse::<u32>().ok()?;
+
+    days_from_civil(year, month, day)
+}
+
+/*
+ * Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+ * http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+ */
+fn days_from_civil(year: u32, month: u32, day: u32) -> Option<u32> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) { return None; }
+
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    (era * 146_097 + day_of_era).checked_sub(719_468)
+}
+
+/*
+ * Coordinates are ddmm.mmmm for latitude and dddmm.mmmm for longitude.
+ */
+fn parse_position(lat: &str, ns: &str, lon: &str, ew: &str) -> Option<Position> {
+    let latitude = parse_coordinate(lat, 2)?;
+    let longitude = parse_coordinate(lon, 3)?;
+
+    Some(Position {
+        latitude: match ns { "N" => latitude, "S" => -latitude, _ => return None },
+        longitude: match ew { "E" => longitude, "W" => -longitude, _ => return None },
+    })
+}
+
+fn parse_coordinate(field: &str, degree_digits: usize) -> Option<f64> {
+    let degrees: f64 = field.get(0..degree_digits)?.parse().ok()?;
+    let minutes: f64 = field.get(degree_digits..)?.parse().ok()?;
+
+    Some(degrees + minutes / 60.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn parses_gga() {
+        let line = "$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47";
+        let Ok(Some(Sentence::Gga(gga))) = parse(line) else {
+            panic!("not a GGA sentence: {:?}", parse(line));
+        };
+
+        assert_eq!(gga.time_of_day, Some(12 * 3600 + 35 * 60 + 19));
+        assert_eq!(gga.quality, 1);
+        assert_eq!(gga.satellites, 8);
+        let position = gga.position.unwrap();
+        assert_near(position.latitude, 48.0 + 7.038 / 60.0);
+        assert_near(position.longitude, 11.0 + 31.0 / 60.0);
+    }
+
+    #[test]
+    fn parses_rmc_from_any_talker() {
+        let line = "$GNRMC,123519,A,4807.038,N,01131.000,W,022.4,084.4,230324,003.1,W*6D";
+        let Ok(Some(Sentence::Rmc(rmc))) = parse(line) else {
+            panic!("not an RMC sentence: {:?}", parse(line));
+        };
+
+        assert!(rmc.valid);
+        // 2024-03-23 12:35:19 UTC
+        assert_eq!(rmc.unix_time, Some(1_711_197_319));
+        assert_near(rmc.position.unwrap().longitude, -(11.0 + 31.0 / 60.0));
+    }
+
+    #[test]
+    fn parses_rmc_without_a_fix() {
+        let Ok(Some(Sentence::Rmc(rmc))) = parse("$GPRMC,,V,,,,,,,,,,N*53") else {
+            panic!("not an RMC sentence");
+        };
+
+        assert!(!rmc.valid);
+        assert_eq!(rmc.unix_time, None);
+        assert_eq!(rmc.position, None);
+    }
+
+    #[test]
+    fn ignores_other_sentences() {
+        assert_eq!(parse("$GPGSV,3,1,11*7B"), Ok(None));
+    }
+
+    #[test]
+    fn rejects_bad_checksum() {
+        let line = "$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*48";
+        assert!(parse(line).is_err());
+    }
+
+    #[test]
+    fn rejects_missing_checksum_and_prefix() {
+        assert!(parse("$GPGGA,123519,4807.038,N,01131.000,E,1,08").is_err());
+        assert!(parse("GPGSV,3,1,11*7B").is_err());
+        assert!(parse("$GPGSV,3,1,11*ZZ").is_err());
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_fields() {
+        assert!(parse("$GPGGA,123519,4807.038*45").is_err());
+    }
+
+    #[test]
+    fn survives_non_ascii_bytes() {
+        // U+FFFD from from_utf8_lossy, with the checksum still matching
+        assert_eq!(parse("$GG\u{FFFD}A,1,2*AF"), Ok(None));
+
+        let line = "$GPGGA,123519,4807.038,N,01131.000,E,1,0\u{FFFD},0.9,545.4,M,46.9,M,,*92";
+        let Ok(Some(Sentence::Gga(gga))) = parse(line) else {
+            panic!("not a GGA sentence: {:?}", parse(line));
+        };
+        assert_eq!(gga.satellites, 0);
+
+        let lossy = String::from_utf8_lossy(b"$GPGGA,\xff\xfe\x80*00");
+        assert!(parse(&lossy).is_err());
+    }
+}
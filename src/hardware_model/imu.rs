@@ -0,0 +1,164 @@
+// https://www.bosch-sensortec.com/media/boschsensortec/downloads/datasheets/bst-bno055-ds000.pdf
+
+use rppal::i2c::I2c;
+use std::{ thread, time::Duration };
+use crate::{
+    config::hardware::imu::ImuConfig,
+    error::{ InitFailure, PeripheralInitError },
+    hardware_model::ComponentKind,
+    telemetry::{ ImuTelemetry, TELEMETRY_PACKET_SIZE },
+    traits::{ Component, SubmarineComponent, Tick },
+};
+
+const CHIP_ID: u8 = 0x00;
+const EULER_HEADING: u8 = 0x1A;
+const CALIB_STAT: u8 = 0x35;
+const OPR_MODE: u8 = 0x3D;
+
+const BNO055_ID: u8 = 0xA0;
+const MODE_CONFIG: u8 = 0x00;
+// 9 degrees of freedom fusion, heading relative to magnetic north
+const MODE_NDOF: u8 = 0x0C;
+
+// euler angles are in 1/16 degree
+const EULER_LSB_PER_DEG: f32 = 16.0;
+
+/*
+ * A BNO055 doing its own sensor fusion, giving absolute orientation. The
+ * heading is in degrees clockwise from north, roll and pitch in degrees.
+ */
+pub struct Imu {
+    i2c: I2c,
+    heading_offset_deg: f32,
+    heading_deg: f32,
+    roll_deg: f32,
+    pitch_deg: f32,
+    // system, gyro, accelerometer and magnetometer, 2 bits each
+    calibration: u8,
+    last_read_valid: bool,
+    enabled: bool,
+}
+
+impl Imu {
+    pub fn new(config: &ImuConfig) -> Result<Self, PeripheralInitError> {
+        let mut i2c = I2c::with_bus(config.bus).map_err(|e| PeripheralInitError {
+            reason: InitFailure::Bus,
+            message: format!("Failed to open I2C bus {}: {}", config.bus, e),
+        })?;
+        i2c.set_slave_address(config.address).map_err(|e| PeripheralInitError {
+            reason: InitFailure::Device,
+            message: format!("Failed to address BNO055 at {:#X}: {}", config.address, e),
+        })?;
+
+        let mut imu = Self {
+            i2c,
+            heading_offset_deg: config.heading_offset_deg,
+            heading_deg: 0.0,
+            roll_deg: 0.0,
+            pitch_deg: 0.0,
+            calibration: 0,
+            last_read_valid: false,
+            enabled: true,
+        };
+        imu.configure().map_err(|e| PeripheralInitError {
+            reason: InitFailure::Device,
+            message: format!("Failed to configure BNO055 at {:#X}: {}", config.address, e),
+        })?;
+
+        Ok(imu)
+    }
+
+    pub fn get_heading(&self) -> Option<f32> {
+        if self.last_read_valid { Some(self.heading_deg) } else { None }
+    }
+
+    pub fn get_roll(&self) -> f32 {
+        self.roll_deg
+    }
+
+    pub fn get_pitch(&self) -> f32 {
+        self.pitch_deg
+    }
+
+    pub fn get_calibration(&self) -> u8 {
+        self.calibration
+    }
+
+    pub fn is_last_read_valid(&self) -> bool {
+        self.last_read_valid
+    }
+
+    fn configure(&mut self) -> Result<(), String> {
+        let mut id = [0u8; 1];
+        self.i2c.write_read(&[CHIP_ID], &mut id).map_err(|e| e.to_string())?;
+        if id[0] != BNO055_ID {
+            return Err(format!("unexpected chip ID {:#X}", id[0]));
+        }
+
+        // mode switches take up to 19ms from config mode, 7ms into it
+        self.i2c.write(&[OPR_MODE, MODE_CONFIG]).map_err(|e| e.to_string())?;
+        thread::sleep(Duration::from_millis(25));
+        self.i2c.write(&[OPR_MODE, MODE_NDOF]).map_err(|e| e.to_string())?;
+        thread::sleep(Duration::from_millis(25));
+
+        Ok(())
+    }
+
+    fn read_orientation(&mut self) -> Result<(), rppal::i2c::Error> {
+        let mut euler = [0u8; 6];
+        self.i2c.write_read(&[EULER_HEADING], &mut euler)?;
+
+        let angle = |lsb: u8, msb: u8| {
+            i16::from_le_bytes([lsb, msb]) as f32 / EULER_LSB_PER_DEG
+        };
+        self.heading_deg =
+            (angle(euler[0], euler[1]) + self.heading_offset_deg).rem_euclid(360.0);
+        self.roll_deg = angle(euler[2], euler[3]);
+        self.pitch_deg = angle(euler[4], euler[5]);
+
+        let mut calibration = [0u8; 1];
+        self.i2c.write_read(&[CALIB_STAT], &mut calibration)?;
+        self.calibration = calibration[0];
+
+        Ok(())
+    }
+}
+
+impl Tick for Imu {
+    fn tick(&mut self, _tick_count: u32) {
+        match self.read_orientation() {
+            Ok(_) => self.last_read_valid = true,
+            Err(e) => {
+                eprintln!("BNO055 Failure: {}", e);
+                self.last_read_valid = false;
+            },
+        }
+    }
+}
+
+impl SubmarineComponent for Imu {
+    fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.last_read_valid = false;
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for Imu {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::Imu
+    }
+
+    fn serialize_telemetry(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        let mut telemetry = ImuTelemetry::new();
+        telemetry.collect(self);
+        telemetry.serialize(buffer)
+    }
+}
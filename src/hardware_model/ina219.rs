@@ -0,0 +1,69 @@
+// https://www.ti.com/lit/ds/symlink/ina219.pdf
+
+use rppal::i2c::I2c;
+use crate::error::{ InitFailure, PeripheralInitError };
+
+const CONFIG_REGISTER: u8 = 0x00;
+const SHUNT_VOLTAGE_REGISTER: u8 = 0x01;
+const BUS_VOLTAGE_REGISTER: u8 = 0x02;
+
+// 32V bus range, 320mV shunt range, 12 bit samples, continuous conversion
+const CONFIG: u16 = 0x399F;
+
+const SHUNT_VOLTAGE_LSB: f32 = 0.00001;
+const BUS_VOLTAGE_LSB: f32 = 0.004;
+
+/*
+ * Current and voltage monitor. The current is computed from the shunt
+ * voltage directly, so the calibration register is left alone.
+ */
+pub struct Ina219 {
+    i2c: I2c,
+    shunt_ohms: f32,
+}
+
+impl Ina219 {
+    pub fn new(bus: u8, address: u16, shunt_ohms: f32)
+        -> Result<Self, PeripheralInitError>
+    {
+        let mut i2c = I2c::with_bus(bus).map_err(|e| PeripheralInitError {
+            reason: InitFailure::Bus,
+            message: format!("Failed to open I2C bus {}: {}", bus, e),
+        })?;
+        i2c.set_slave_address(address).map_err(|e| PeripheralInitError {
+            reason: InitFailure::Device,
+            message: format!("Failed to address INA219 at {:#X}: {}", address, e),
+        })?;
+
+        let config = CONFIG.to_be_bytes();
+        i2c.write(&[CONFIG_REGISTER, config[0], config[1]]).map_err(|e| {
+            PeripheralInitError {
+                reason: InitFailure::Device,
+                message: format!("Failed to configure INA219 at {:#X}: {}", address, e),
+            }
+        })?;
+
+        Ok(Self { i2c, shunt_ohms })
+    }
+
+    pub fn read_current(&mut self) -> Result<f32, String> {
+        let shunt = self.read_register(SHUNT_VOLTAGE_REGISTER)? as i16;
+
+        Ok(shunt as f32 * SHUNT_VOLTAGE_LSB / self.shunt_ohms)
+    }
+
+    pub fn read_bus_voltage(&mut self) -> Result<f32, String> {
+        let bus = self.read_register(BUS_VOLTAGE_REGISTER)?;
+
+        Ok((bus >> 3) as f32 * BUS_VOLTAGE_LSB)
+    }
+
+    fn read_register(&mut self, register: u8) -> Result<u16, String> {
+        let mut buffer = [0u8; 2];
+
+        self.i2c.write_read(&[register], &mut buffer)
+            .map_err(|e| e.to_string())?;
+
+        Ok(u16::from_be_bytes(buffer))
+    }
+}
@@ -0,0 +1,195 @@
+use rppal::gpio::{ Gpio, InputPin, Level, Trigger };
+use std::{
+    sync::{ Arc, Mutex },
+    time::{ Duration, Instant },
+};
+use crate::{
+    action::{ self, Action },
+    config::hardware::input::{ InputConfig, InputPull },
+    error::{ InitFailure, PeripheralInitError },
+    hardware_model::ComponentKind,
+    telemetry::{ InputTelemetry, TELEMETRY_PACKET_SIZE },
+    traits::{ Component, SubmarineComponent, Tick },
+};
+
+/*
+ * Edges seen by the interrupt handler since the last tick.
+ */
+#[derive(Debug, Default)]
+struct EdgeEvents {
+    active: bool,
+    edge_count: u32,
+    activations: u32,
+    deactivations: u32,
+    last_edge: Option<Instant>,
+}
+
+impl EdgeEvents {
+    fn record(&mut self, now_active: bool) {
+        self.active = now_active;
+        self.edge_count = self.edge_count.wrapping_add(1);
+        self.last_edge = Some(Instant::now());
+        if now_active {
+            self.activations += 1;
+        } else {
+            self.deactivations += 1;
+        }
+    }
+}
+
+/*
+ * A named GPIO input, such as a switch, leak probe or flow meter. Edges
+ * are caught by an interrupt rather than polled, so short pulses are not
+ * missed between ticks, and are debounced by time. Edges inside the
+ * debounce window are dropped, so the pin is read again once the window
+ * has passed in case the last one was real.
+ */
+pub struct Input {
+    // also keeps the interrupt registered
+    pin: InputPin,
+    active_level: Level,
+    debounce: Duration,
+    events: Arc<Mutex<EdgeEvents>>,
+    on_activate: Vec<Action>,
+    on_deactivate: Vec<Action>,
+    active: bool,
+    edge_count: u32,
+    enabled: bool,
+}
+
+impl Input {
+    pub fn new(config: &InputConfig) -> Result<Self, PeripheralInitError> {
+        let pin = Gpio::new().map_err(|e| {
+            PeripheralInitError{
+                reason: InitFailure::Gpio,
+                message: format!(
+                    "Failed to init Gpio for pin {}: {}",
+                    config.pin,
+                    e.to_string()
+                )
+            }
+        })?.get(config.pin).map_err(|e| {
+            PeripheralInitError {
+                reason: InitFailure::Gpio,
+                message: format!(
+                    "Failed to get gpio pin {}: {}",
+                    config.pin,
+                    e.to_string()
+                )
+            }
+        })?;
+
+        let mut pin = match config.pull {
+            InputPull::Up => pin.into_input_pullup(),
+            InputPull::Down => pin.into_input_pulldown(),
+            InputPull::None => pin.into_input(),
+        };
+
+        let active_level = if config.active_low { Level::Low } else { Level::High };
+        let active = pin.read() == active_level;
+        let events = Arc::new(Mutex::new(EdgeEvents {
+            active,
+            ..Default::default()
+        }));
+
+        let interrupt_events = events.clone();
+        let debounce = Duration::from_millis(config.debounce_ms as u64);
+
+        pin.set_async_interrupt(Trigger::Both, move |level| {
+            let mut events = interrupt_events.lock().unwrap();
+            let now_active = level == active_level;
+            let bouncing = events.last_edge
+                .map_or(false, |t| t.elapsed() < debounce);
+
+            if bouncing || now_active == events.active { return; }
+
+            events.record(now_active);
+        }).map_err(|e| PeripheralInitError {
+            reason: InitFailure::Gpio,
+            message: format!("Failed to set interrupt on pin {}: {}", config.pin, e),
+        })?;
+
+        Ok(Self {
+            pin,
+            active_level,
+            debounce,
+            events,
+            on_activate: config.on_activate.clone(),
+            on_deactivate: config.on_deactivate.clone(),
+            active,
+            edge_count: 0,
+            enabled: true,
+        })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn get_edge_count(&self) -> u32 {
+        self.edge_count
+    }
+}
+
+impl Tick for Input {
+    /*
+     * Actions run once per tick at most, however many edges came in,
+     * so a chattering input can't flood the controller.
+     */
+    fn tick(&mut self, _tick_count: u32) {
+        let (activations, deactivations) = {
+            let mut events = self.events.lock().unwrap();
+
+            let settled = events.last_edge.map_or(true, |t| t.elapsed() >= self.debounce);
+            let now_active = self.pin.read() == self.active_level;
+            if settled && now_active != events.active {
+                events.record(now_active);
+            }
+
+            self.active = events.active;
+            self.edge_count = events.edge_count;
+
+            let counts = (events.activations, events.deactivations);
+            events.activations = 0;
+            events.deactivations = 0;
+            counts
+        };
+
+        if activations > 0 {
+            self.on_activate.iter().for_each(action::trigger);
+        }
+        if deactivations > 0 {
+            self.on_deactivate.iter().for_each(action::trigger);
+        }
+    }
+}
+
+impl SubmarineComponent for Input {
+    fn enable(&mut self) {
+        // edges while disabled don't run actions
+        let mut events = self.events.lock().unwrap();
+        events.activations = 0;
+        events.deactivations = 0;
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for Input {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::Input
+    }
+
+    fn serialize_telemetry(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        let mut telemetry = InputTelemetry::new();
+        telemetry.collect(self);
+        telemetry.serialize(buffer)
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{
+    config::hardware::kill_switch::KillSwitchConfig,
+    error::PeripheralInitError,
+};
+use super::debounced_input::DebouncedInput;
+
+pub struct KillSwitch {
+    input: DebouncedInput,
+    armed: bool,
+    arm_changes: u16,
+}
+
+impl KillSwitch {
+    pub fn new(config: &KillSwitchConfig) -> Result<Self, PeripheralInitError> {
+        let input = DebouncedInput::new(
+            config.pin,
+            config.active_low,
+            config.debounce_ticks
+        )?;
+        let armed = input.is_active();
+
+        println!("Kill switch is {}.", if armed { "armed" } else { "disarmed" });
+
+        Ok(Self {
+            input,
+            armed,
+            arm_changes: 0,
+        })
+    }
+
+    /*
+     * Samples the switch, logging any change in arming state.
+     */
+    pub fn update(&mut self) -> bool {
+        let armed = self.input.update();
+
+        if armed != self.armed {
+            println!("Kill switch {}.", if armed { "armed" } else { "disarmed" });
+            self.arm_changes = self.arm_changes.wrapping_add(1);
+            self.armed = armed;
+        }
+
+        self.armed
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.armed
+    }
+
+    pub fn get_arm_changes(&self) -> u16 {
+        self.arm_changes
+    }
+}
@@ -1,8 +1,10 @@
 use rppal::gpio::{ OutputPin, Gpio };
 use crate::{
+    command::Command,
     config::hardware::light::LightConfig,
-    error::PeripheralInitError,
-    traits::Tick,
+    error::{ InitFailure, PeripheralInitError },
+    hardware_model::ComponentKind,
+    traits::{ Component, SubmarineComponent, Tick },
 };
 use common::commands::LightCommand;
 
@@ -10,11 +12,16 @@ enum State {
     On,
     Off,
     Blink,
+    Beacon,
 }
 
+// ticks in one beacon cycle, the light flashes twice at the start of it
+const BEACON_PERIOD_TICKS: u32 = 20;
+
 pub struct Light {
     en_pin: OutputPin,
     state: State,
+    enabled: bool,
 }
 
 impl Light {
@@ -22,6 +29,7 @@ impl Light {
         Ok(Self{
             en_pin: Gpio::new().map_err(|e| {
                 PeripheralInitError{
+                    reason: InitFailure::Gpio,
                     message: format!(
                         "Failed to init Gpio for pin {}: {}",
                         config.gpio.light_pin,
@@ -30,6 +38,7 @@ impl Light {
                 }
             })?.get(config.gpio.light_pin).map_err(|e| {
                 PeripheralInitError {
+                    reason: InitFailure::Gpio,
                     message: format!(
                         "Failed to get gpio pin {}: {}",
                         config.gpio.light_pin,
@@ -39,6 +48,7 @@ impl Light {
             })?.into_output(),
 
             state: State::Off,
+            enabled: true,
         })
     }
 
@@ -61,6 +71,10 @@ impl Light {
     fn blink(&mut self) {
         self.state = State::Blink;
     }
+
+    pub fn beacon(&mut self) {
+        self.state = State::Beacon;
+    }
 }
 
 impl Tick for Light {
@@ -72,7 +86,39 @@ impl Tick for Light {
                 if tick_count % 10 == 0 {
                     self.en_pin.toggle();
                 }
-            }
+            },
+            State::Beacon => match tick_count % BEACON_PERIOD_TICKS {
+                0 | 2 => self.en_pin.set_high(),
+                _ => self.en_pin.set_low(),
+            },
+        }
+    }
+}
+
+impl SubmarineComponent for Light {
+    fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.turn_off();
+        self.en_pin.set_low();
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for Light {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::Light
+    }
+
+    fn handle(&mut self, cmd: &Command) {
+        if let Command::Light(c) = cmd {
+            self.handle_command(c.as_ref());
         }
     }
 }
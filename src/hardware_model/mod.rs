@@ -1,39 +1,502 @@
 mod ballast;
+mod battery;
+mod debounced_input;
+mod debug_led;
+mod depth_sensor;
+mod echo_sounder;
+mod gps;
+mod imu;
 mod light;
 mod propulsion;
 mod dht11;
+mod ds18b20;
+mod ina219;
+mod input;
+mod kill_switch;
+mod registry;
+mod serial_port;
+mod servo;
 
-use crate::{ traits::Tick, config::hardware::HardwareConfig };
-use ballast::Ballast;
-use light::Light;
-use propulsion::Propulsion;
-use dht11::Dht11;
+use crate::{
+    action::{ self, Action, BallastAction },
+    traits::{ Component, SubmarineComponent, Tick },
+    config::{ control::ControlConfig, hardware::HardwareConfig },
+    command::{ self, CommandSource },
+    control::{
+        AutoTrim,
+        DeadReckoning,
+        DepthHold,
+        Envelope,
+        HeadingHold,
+        LinkMonitor,
+        LinkState,
+        MissionRunner,
+        ModeMachine,
+        RuleEngine,
+        VehicleMode,
+    },
+    error::PeripheralInitError,
+};
+use common::commands::LightCommand;
+use ballast::BallastState;
+
+pub use ballast::Ballast;
+pub use battery::Battery;
+pub use debug_led::{ DebugLed, StatusCode };
+pub use depth_sensor::DepthSensor;
+pub use echo_sounder::EchoSounder;
+pub use gps::Gps;
+pub use imu::Imu;
+pub use input::Input;
+pub use kill_switch::KillSwitch;
+pub use light::Light;
+pub use propulsion::{ Propulsion, ThrusterFault };
+pub use dht11::Dht11;
+pub use ds18b20::Ds18b20;
+pub use registry::ComponentRegistry;
+pub use servo::Servo;
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum ComponentKind {
+    Ballast = 0x0,
+    DebugLed = 0x1,
+    Light = 0x2,
+    Propulsion = 0x3,
+    Dht11 = 0x4,
+    Servo = 0x5,
+    EchoSounder = 0x6,
+    Gps = 0x7,
+    Ds18b20 = 0x8,
+    Input = 0x9,
+    DepthSensor = 0xA,
+    Imu = 0xB,
+    Battery = 0xC,
+}
 
 pub struct Submarine {
-    pub ballast: Ballast,
-    pub light: Light,
-    pub propulsion: Propulsion,
-    pub dht11: Dht11,
+    components: ComponentRegistry,
+    kill_switch: Option<KillSwitch>,
+    depth_hold: Option<DepthHold>,
+    heading_hold: Option<HeadingHold>,
+    auto_trim: Option<AutoTrim>,
+    mode: ModeMachine,
+    link: Option<LinkMonitor>,
+    failsafe_actions: Vec<Action>,
+    missions: MissionRunner,
+    envelope: Envelope,
+    dead_reckoning: Option<DeadReckoning>,
+    rules: RuleEngine,
+    // who latched the E-stop, None when it isn't latched
+    emergency_stop: Option<CommandSource>,
+    emergency_stops: u16,
 }
 
 impl Submarine {
-    pub fn new(config: &HardwareConfig)
-        -> Result<Submarine, crate::error::PeripheralInitError>
+    pub fn new(config: &HardwareConfig, control: &ControlConfig, tick_rate: u8)
+        -> Result<Submarine, PeripheralInitError>
     {
+        let tick_interval_s = 1.0 / tick_rate as f32;
+        let mut components = ComponentRegistry::new();
+
+        // register new components here
+        for c in config.ballast.iter() {
+            components.register(&c.name, ComponentKind::Ballast, c.required,
+                || Ballast::new(c))?;
+        }
+        for c in config.debug.iter() {
+            components.register(&c.name, ComponentKind::DebugLed, c.required,
+                || DebugLed::new(c))?;
+        }
+        for c in config.light.iter() {
+            components.register(&c.name, ComponentKind::Light, c.required,
+                || Light::new(c))?;
+        }
+        for c in config.propulsion.iter() {
+            components.register(&c.name, ComponentKind::Propulsion, c.required,
+                || Propulsion::new(c))?;
+        }
+        for c in config.dht11.iter() {
+            components.register(&c.name, ComponentKind::Dht11, c.required,
+                || Dht11::new(c))?;
+        }
+        for c in config.ds18b20.iter() {
+            components.register(&c.name, ComponentKind::Ds18b20, c.required,
+                || Ds18b20::new(c))?;
+        }
+        for c in config.servo.iter() {
+            components.register(&c.name, ComponentKind::Servo, c.required,
+                || Servo::new(c))?;
+        }
+        for c in config.echo_sounder.iter() {
+            components.register(&c.name, ComponentKind::EchoSounder, c.required,
+                || EchoSounder::new(c))?;
+        }
+        for c in config.gps.iter() {
+            components.register(&c.name, ComponentKind::Gps, c.required,
+                || Gps::new(c))?;
+        }
+        for c in config.depth_sensor.iter() {
+            components.register(&c.name, ComponentKind::DepthSensor, c.required,
+                || DepthSensor::new(c))?;
+        }
+        for c in config.imu.iter() {
+            components.register(&c.name, ComponentKind::Imu, c.required,
+                || Imu::new(c))?;
+        }
+        for c in config.battery.iter() {
+            components.register(&c.name, ComponentKind::Battery, c.required,
+                || Battery::new(c))?;
+        }
+        for c in config.input.iter() {
+            components.register(&c.name, ComponentKind::Input, c.required,
+                || Input::new(c))?;
+        }
+
         Ok(Submarine {
-            ballast: Ballast::new(&config.ballast)?,
-            light: Light::new(&config.light)?,
-            propulsion: Propulsion::new(&config.propulsion)?,
-            dht11: Dht11::new(&config.dht11)?,
+            components,
+            kill_switch: config.kill_switch.as_ref()
+                .map(KillSwitch::new)
+                .transpose()?,
+            depth_hold: control.depth_hold.as_ref()
+                .map(|c| DepthHold::new(c, tick_interval_s))
+                .transpose()?,
+            heading_hold: control.heading_hold.as_ref()
+                .map(|c| HeadingHold::new(c, tick_interval_s))
+                .transpose()?,
+            auto_trim: control.auto_trim.as_ref()
+                .map(|c| AutoTrim::new(c, tick_interval_s)),
+            mode: ModeMachine::new(control.initial_mode),
+            link: control.link.as_ref().map(LinkMonitor::new),
+            failsafe_actions: control.failsafe.actions.clone(),
+            missions: MissionRunner::new(control.missions.clone(), tick_interval_s),
+            envelope: Envelope::new(&control.envelope, tick_interval_s),
+            dead_reckoning: control.dead_reckoning.as_ref()
+                .map(|c| DeadReckoning::new(c, tick_interval_s)),
+            rules: RuleEngine::new(&control.rules),
+            emergency_stop: None,
+            emergency_stops: 0,
         })
     }
+
+    pub fn components(&self) -> &ComponentRegistry {
+        &self.components
+    }
+
+    pub fn components_mut(&mut self) -> &mut ComponentRegistry {
+        &mut self.components
+    }
+
+    pub fn kill_switch(&self) -> Option<&KillSwitch> {
+        self.kill_switch.as_ref()
+    }
+
+    pub fn depth_hold(&self) -> Option<&DepthHold> {
+        self.depth_hold.as_ref()
+    }
+
+    pub fn depth_hold_mut(&mut self) -> Option<&mut DepthHold> {
+        self.depth_hold.as_mut()
+    }
+
+    pub fn disengage_depth_hold(&mut self) {
+        if let Some(d) = self.depth_hold.as_mut() {
+            d.disengage(&mut self.components);
+        }
+    }
+
+    pub fn heading_hold(&self) -> Option<&HeadingHold> {
+        self.heading_hold.as_ref()
+    }
+
+    pub fn heading_hold_mut(&mut self) -> Option<&mut HeadingHold> {
+        self.heading_hold.as_mut()
+    }
+
+    pub fn disengage_heading_hold(&mut self) {
+        if let Some(h) = self.heading_hold.as_mut() {
+            h.disengage(&mut self.components);
+        }
+    }
+
+    pub fn auto_trim(&self) -> Option<&AutoTrim> {
+        self.auto_trim.as_ref()
+    }
+
+    /*
+     * Trimming and holding depth both drive the ballast, so starting one
+     * stops the other. The thrusters are stopped too, and kept off until
+     * the trim is done.
+     */
+    pub fn start_auto_trim(&mut self) -> Result<(), String> {
+        let Some(t) = self.auto_trim.as_mut() else {
+            return Err("not configured".to_string());
+        };
+        if let Some(d) = self.depth_hold.as_mut() {
+            d.disengage(&mut self.components);
+        }
+        if let Some(h) = self.heading_hold.as_mut() {
+            h.disengage(&mut self.components);
+        }
+        for p in self.components.iter_mut_of::<Propulsion>() {
+            p.zero_thrust();
+        }
+        t.start(&mut self.components);
+
+        Ok(())
+    }
+
+    pub fn abort_auto_trim(&mut self) {
+        if let Some(t) = self.auto_trim.as_mut() {
+            t.abort(&mut self.components);
+        }
+    }
+
+    pub fn disengage_autopilots(&mut self) {
+        self.disengage_depth_hold();
+        self.disengage_heading_hold();
+        self.abort_auto_trim();
+    }
+
+    pub fn mode(&self) -> &ModeMachine {
+        &self.mode
+    }
+
+    pub fn mode_mut(&mut self) -> &mut ModeMachine {
+        &mut self.mode
+    }
+
+    pub fn link(&self) -> Option<&LinkMonitor> {
+        self.link.as_ref()
+    }
+
+    pub fn missions(&self) -> &MissionRunner {
+        &self.missions
+    }
+
+    pub fn missions_mut(&mut self) -> &mut MissionRunner {
+        &mut self.missions
+    }
+
+    pub fn envelope(&self) -> &Envelope {
+        &self.envelope
+    }
+
+    pub fn dead_reckoning(&self) -> Option<&DeadReckoning> {
+        self.dead_reckoning.as_ref()
+    }
+
+    pub fn rules(&self) -> &RuleEngine {
+        &self.rules
+    }
+
+    pub fn run_mission_actions(&mut self, actions: Vec<Action>) {
+        for a in actions.iter() {
+            action::run_as(a, self, CommandSource::Mission);
+        }
+    }
+
+    /*
+     * Changes mode and runs the exit and entry actions. The vehicle can't
+     * leave Disarmed while the kill switch is safed, or be driven while
+     * the link is lost.
+     */
+    pub fn set_mode(&mut self, next: VehicleMode, reason: &str) -> Result<(), String> {
+        if self.mode.get_mode() == VehicleMode::Disarmed && !self.is_armed() {
+            return Err("the kill switch is not armed".to_string());
+        }
+        let link_lost = self.link.as_ref()
+            .map_or(false, |l| l.get_state() == LinkState::Lost);
+        if link_lost && next != VehicleMode::Disarmed && next != VehicleMode::Failsafe {
+            return Err("the link is lost".to_string());
+        }
+        let breached = self.envelope.is_breached();
+        if breached && next != VehicleMode::Disarmed && next != VehicleMode::Failsafe {
+            return Err("the envelope is breached".to_string());
+        }
+
+        self.mode.transition(next, reason)?;
+
+        if !next.allows_autopilot() {
+            self.disengage_autopilots();
+        }
+        if next != VehicleMode::Autonomous {
+            let actions = self.missions.abort("left autonomous mode");
+            self.run_mission_actions(actions);
+        }
+
+        let (stop, ballast, light) = match next {
+            VehicleMode::Disarmed =>
+                (true, Some(BallastAction::Idle), Some(LightCommand::Off)),
+            VehicleMode::Manual | VehicleMode::Assisted =>
+                (true, Some(BallastAction::Idle), Some(LightCommand::On)),
+            VehicleMode::Autonomous => (false, None, Some(LightCommand::Blink)),
+            // the rest is configured, see below
+            VehicleMode::Failsafe => (true, None, None),
+        };
+
+        if stop {
+            for p in self.components.iter_mut_of::<Propulsion>() {
+                p.zero_thrust();
+            }
+        }
+        // the ballast keeps blowing while the envelope is breached
+        if let Some(action) = ballast.filter(|_| !breached) {
+            let command = action.to_command();
+            for b in self.components.iter_mut_of::<Ballast>() {
+                if b.is_enabled() { b.handle(&command); }
+            }
+        }
+        if let Some(light) = light {
+            for l in self.components.iter_mut_of::<Light>() {
+                if l.is_enabled() { l.handle_command(&light); }
+            }
+        }
+        if next == VehicleMode::Failsafe {
+            for a in self.failsafe_actions.clone().iter() {
+                action::run(a, self);
+            }
+        }
+
+        Ok(())
+    }
+
+    /*
+     * Stops the thrusters and blows the ballast, straight to the
+     * components, since the envelope overrides any mode. Run every tick
+     * while breached, so nothing can cancel the ascent.
+     */
+    fn force_surface(&mut self) {
+        let mode = self.mode.get_mode();
+        if mode != VehicleMode::Disarmed && mode != VehicleMode::Failsafe {
+            let _ = self.set_mode(VehicleMode::Failsafe, "envelope breached");
+        }
+        self.disengage_autopilots();
+
+        for p in self.components.iter_mut_of::<Propulsion>() {
+            p.zero_thrust();
+        }
+        // every ballast command restarts the transition, so only resend it
+        let command = BallastAction::Discharge.to_command();
+        for b in self.components.iter_mut_of::<Ballast>() {
+            if b.is_enabled() && b.get_target_state() != BallastState::Discharge {
+                b.handle(&command);
+            }
+        }
+    }
+
+    /*
+     * Latches the E-stop and inhibits every component at once, which stops
+     * the thrusters and idles the ballast without waiting for the tick.
+     */
+    pub fn emergency_stop(&mut self, source: CommandSource) {
+        self.disengage_autopilots();
+        let actions = self.missions.abort("emergency stop");
+        self.run_mission_actions(actions);
+
+        if self.emergency_stop.is_none() {
+            eprintln!("Emergency stop from {:?}.", source);
+            self.emergency_stop = Some(source);
+            self.emergency_stops = self.emergency_stops.wrapping_add(1);
+        }
+
+        for slot in self.components.slots_mut() {
+            if let Some(c) = slot.component_mut() { c.set_inhibited(true); }
+        }
+    }
+
+    pub fn reset_emergency_stop(&mut self) {
+        if self.emergency_stop.take().is_some() {
+            println!("Emergency stop reset.");
+        }
+    }
+
+    pub fn get_emergency_stop(&self) -> Option<CommandSource> {
+        self.emergency_stop
+    }
+
+    pub fn get_emergency_stops(&self) -> u16 {
+        self.emergency_stops
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.kill_switch.as_ref().map_or(true, |k| k.is_armed())
+    }
+
+    pub fn is_actuation_inhibited(&self) -> bool {
+        !self.is_armed()
+            || self.mode.get_mode().inhibits_actuators()
+            || self.emergency_stop.is_some()
+    }
+
+    pub fn set_status(&mut self, code: StatusCode, active: bool) {
+        for led in self.components.iter_mut_of::<DebugLed>() {
+            led.set_status(code, active);
+        }
+    }
 }
 
 impl Tick for Submarine {
     fn tick(&mut self, tick_count: u32) {
-        self.ballast.tick(tick_count);
-        self.light.tick(tick_count);
-        self.propulsion.tick(tick_count);
-        self.dht11.tick(tick_count);
+        if let Some(k) = self.kill_switch.as_mut() { k.update(); }
+        if !self.is_armed() && self.mode.get_mode() != VehicleMode::Disarmed {
+            let _ = self.set_mode(VehicleMode::Disarmed, "kill switch safed");
+        }
+        self.mode.tick();
+
+        let link_lost = self.link.as_mut()
+            .map_or(false, |l| l.update(command::last_heartbeat()));
+        let mode = self.mode.get_mode();
+        if link_lost && mode != VehicleMode::Disarmed && mode != VehicleMode::Failsafe {
+            let _ = self.set_mode(VehicleMode::Failsafe, "link lost");
+        }
+        let inhibited = self.is_actuation_inhibited();
+
+        // absent and disabled components are not ticked
+        for slot in self.components.slots_mut() {
+            let Some(c) = slot.component_mut() else { continue };
+            c.set_inhibited(inhibited);
+
+            if c.is_enabled() { c.tick(tick_count); }
+        }
+
+        if let Some(d) = self.dead_reckoning.as_mut() { d.update(&self.components); }
+
+        self.envelope.update(&self.components);
+        if self.envelope.is_breached() {
+            self.force_surface();
+        }
+
+        // the failsafe beacon is left alone, whatever the rules say
+        let failsafe = self.mode.get_mode() == VehicleMode::Failsafe;
+        let actions = self.rules.update(&self.components);
+        for a in actions.iter() {
+            if failsafe && matches!(a, Action::Light { .. }) { continue; }
+            action::run(a, self);
+        }
+
+        if self.mode.get_mode() == VehicleMode::Autonomous {
+            let actions = self.missions.update(&self.components);
+            self.run_mission_actions(actions);
+        }
+
+        // controllers run on this tick's sensor readings
+        if inhibited {
+            self.disengage_autopilots();
+        }
+        if let Some(d) = self.depth_hold.as_mut() { d.update(&mut self.components); }
+        if let Some(h) = self.heading_hold.as_mut() { h.update(&mut self.components); }
+        if let Some(t) = self.auto_trim.as_mut() { t.update(&mut self.components); }
+
+        let sensor_failure = self.components.slots().iter()
+            .filter(|s| s.kind == ComponentKind::Dht11)
+            .any(|s| s.component()
+                .and_then(|c| c.as_any().downcast_ref::<Dht11>())
+                .map_or(true, |dht11| !dht11.is_last_read_valid()))
+            || self.components.slots().iter()
+            .filter(|s| s.kind == ComponentKind::Ds18b20)
+            .any(|s| s.component()
+                .and_then(|c| c.as_any().downcast_ref::<Ds18b20>())
+                .map_or(true, |probe| !probe.is_reading_valid()));
+        self.set_status(StatusCode::SensorFailure, sensor_failure);
     }
 }
@@ -0,0 +1,222 @@
+use rppal::spi::{ Bus, Mode, SlaveSelect, Spi };
+use crate::{
+    config::hardware::propulsion::{ CurrentSenseConfig, CurrentSensorConfig },
+    error::{ InitFailure, PeripheralInitError },
+    hardware_model::ina219::Ina219,
+};
+
+const MCP3008_CLOCK_HZ: u32 = 1_000_000;
+const MCP3008_MAX_READING: f32 = 1023.0;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ThrusterFault {
+    None = 0x0,
+    Stall = 0x1,
+    OpenCircuit = 0x2,
+    SensorError = 0x3,
+}
+
+trait CurrentSensor {
+    fn read_current(&mut self) -> Result<f32, String>;
+}
+
+impl CurrentSensor for Ina219 {
+    fn read_current(&mut self) -> Result<f32, String> {
+        Ina219::read_current(self)
+    }
+}
+
+/*
+ * A current sense amplifier on one channel of an MCP3008.
+ */
+struct AdcSensor {
+    spi: Spi,
+    channel: u8,
+    reference_v: f32,
+    zero_offset_v: f32,
+    volts_per_amp: f32,
+}
+
+impl AdcSensor {
+    fn new(
+        spi_bus: u8,
+        chip_select: u8,
+        channel: u8,
+        reference_v: f32,
+        zero_offset_v: f32,
+        volts_per_amp: f32,
+    ) -> Result<Self, PeripheralInitError> {
+        let bus = match spi_bus {
+            0 => Bus::Spi0,
+            1 => Bus::Spi1,
+            _ => return Err(PeripheralInitError {
+                reason: InitFailure::Bus,
+                message: format!("Invalid SPI bus {}", spi_bus),
+            }),
+        };
+        let slave = match chip_select {
+            0 => SlaveSelect::Ss0,
+            1 => SlaveSelect::Ss1,
+            2 => SlaveSelect::Ss2,
+            _ => return Err(PeripheralInitError {
+                reason: InitFailure::Bus,
+                message: format!("Invalid SPI chip select {}", chip_select),
+            }),
+        };
+        if channel > 7 || volts_per_amp == 0.0 {
+            return Err(PeripheralInitError {
+                reason: InitFailure::Config,
+                message: format!("Invalid ADC current sensor on channel {}", channel),
+            });
+        }
+
+        Ok(Self {
+            spi: Spi::new(bus, slave, MCP3008_CLOCK_HZ, Mode::Mode0).map_err(|e| {
+                PeripheralInitError {
+                    reason: InitFailure::Bus,
+                    message: format!("Failed to open SPI bus {}: {}", spi_bus, e),
+                }
+            })?,
+            channel,
+            reference_v,
+            zero_offset_v,
+            volts_per_amp,
+        })
+    }
+}
+
+impl CurrentSensor for AdcSensor {
+    fn read_current(&mut self) -> Result<f32, String> {
+        // start bit, single ended mode and channel, then clock out the result
+        let write = [0x01, (0x08 | self.channel) << 4, 0x00];
+        let mut read = [0u8; 3];
+
+        self.spi.transfer(&mut read, &write).map_err(|e| e.to_string())?;
+
+        let raw = (((read[1] & 0x03) as u16) << 8) | read[2] as u16;
+        let volts = raw as f32 / MCP3008_MAX_READING * self.reference_v;
+
+        Ok((volts - self.zero_offset_v) / self.volts_per_amp)
+    }
+}
+
+/*
+ * Compares a thruster's measured current against what its commanded
+ * output should draw. A thruster that is off should draw nothing, so it is
+ * only checked while on, once it has had time to spin up.
+ */
+pub struct CurrentMonitor {
+    sensor: Box<dyn CurrentSensor>,
+    stall_current: f32,
+    open_circuit_current: f32,
+    fault_ticks: u8,
+    spin_up_ticks: u8,
+    ticks_on: u8,
+    ticks_out_of_range: u8,
+    current: f32,
+    fault: ThrusterFault,
+}
+
+impl CurrentMonitor {
+    pub fn new(
+        config: &CurrentSenseConfig,
+        sensor: &CurrentSensorConfig,
+    ) -> Result<Self, PeripheralInitError> {
+        let sensor: Box<dyn CurrentSensor> = match *sensor {
+            CurrentSensorConfig::Ina219 { bus, address, shunt_ohms } =>
+                Box::new(Ina219::new(bus, address, shunt_ohms)?),
+            CurrentSensorConfig::Adc {
+                spi_bus,
+                chip_select,
+                channel,
+                reference_v,
+                zero_offset_v,
+                volts_per_amp,
+            } => Box::new(AdcSensor::new(
+                spi_bus,
+                chip_select,
+                channel,
+                reference_v,
+                zero_offset_v,
+                volts_per_amp,
+            )?),
+        };
+
+        Ok(Self {
+            sensor,
+            stall_current: config.stall_current,
+            open_circuit_current: config.open_circuit_current,
+            fault_ticks: config.fault_ticks,
+            spin_up_ticks: config.spin_up_ticks,
+            ticks_on: 0,
+            ticks_out_of_range: 0,
+            current: 0.0,
+            fault: ThrusterFault::None,
+        })
+    }
+
+    /*
+     * Takes a sample and returns the fault, if any, raised by it. A
+     * latched stall stays until cleared.
+     */
+    pub fn sample(&mut self, powered: bool) -> ThrusterFault {
+        match self.sensor.read_current() {
+            Ok(current) => self.current = current,
+            Err(e) => {
+                eprintln!("Failed to read thruster current: {}", e);
+                if self.fault != ThrusterFault::Stall {
+                    self.fault = ThrusterFault::SensorError;
+                }
+                return self.fault;
+            }
+        }
+
+        if self.fault == ThrusterFault::Stall { return self.fault; }
+
+        if !powered {
+            self.ticks_on = 0;
+            self.ticks_out_of_range = 0;
+            return self.fault;
+        }
+
+        if self.ticks_on < self.spin_up_ticks {
+            self.ticks_on += 1;
+            return self.fault;
+        }
+
+        let fault = if self.current >= self.stall_current {
+            ThrusterFault::Stall
+        } else if self.current <= self.open_circuit_current {
+            ThrusterFault::OpenCircuit
+        } else {
+            ThrusterFault::None
+        };
+
+        if fault == ThrusterFault::None {
+            self.ticks_out_of_range = 0;
+            self.fault = ThrusterFault::None;
+        } else {
+            self.ticks_out_of_range = self.ticks_out_of_range.saturating_add(1);
+
+            if self.ticks_out_of_range >= self.fault_ticks {
+                self.fault = fault;
+            }
+        }
+
+        self.fault
+    }
+
+    pub fn clear_fault(&mut self) {
+        self.fault = ThrusterFault::None;
+        self.ticks_on = 0;
+        self.ticks_out_of_range = 0;
+    }
+
+    pub fn get_current(&self) -> f32 {
+        self.current
+    }
+
+    pub fn get_fault(&self) -> ThrusterFault {
+        self.fault
+    }
+}
@@ -1,11 +1,20 @@
+mod current_monitor;
 mod thruster_controller;
 
+pub use current_monitor::ThrusterFault;
 use thruster_controller::ThrusterController;
 use crate::{
-    traits::Tick,
+    traits::{ Component, SubmarineComponent, Tick },
+    command::Command,
     error::PeripheralInitError,
-    config::hardware::propulsion::PropulsionConfig,
+    config::hardware::propulsion::{
+        CurrentSenseConfig,
+        CurrentSensorConfig,
+        PropulsionConfig,
+    },
     definitions::DirectionVector,
+    hardware_model::ComponentKind,
+    telemetry::{ PropulsionTelemetry, TELEMETRY_PACKET_SIZE },
 };
 use common::commands::PropulsionCommand;
 
@@ -14,15 +23,33 @@ pub struct Propulsion {
     starboard_thruster: ThrusterController,
     port_thruster: ThrusterController,
     vector: DirectionVector,
+    // set while an autopilot is steering
+    yaw_override: Option<f32>,
+    enabled: bool,
+    inhibited: bool,
 }
 
 impl Propulsion {
     pub fn new(config: &PropulsionConfig) -> Result<Self, PeripheralInitError> {
+        let sense = config.current_sense.as_ref();
+        let sensor = |select: fn(&CurrentSenseConfig) -> &Option<CurrentSensorConfig>| {
+            sense.and_then(|s| select(s).as_ref().map(|sensor| (s, sensor)))
+        };
+
         Ok(Self {
-            aft_thruster: ThrusterController::new(config.gpio.aft_pin)?,
-            starboard_thruster: ThrusterController::new(config.gpio.starboard_pin)?,
-            port_thruster: ThrusterController::new(config.gpio.port_pin)?,
+            aft_thruster: ThrusterController::new(
+                config.gpio.aft_pin, sensor(|s| &s.aft)
+            )?,
+            starboard_thruster: ThrusterController::new(
+                config.gpio.starboard_pin, sensor(|s| &s.starboard)
+            )?,
+            port_thruster: ThrusterController::new(
+                config.gpio.port_pin, sensor(|s| &s.port)
+            )?,
             vector: DirectionVector{x: 0.0, y: 0.0},
+            yaw_override: None,
+            enabled: true,
+            inhibited: false,
         })
     }
 
@@ -30,15 +57,34 @@ impl Propulsion {
         println!("{:?}", cmd);
 
         match cmd {
-            PropulsionCommand::SetThrust(v) => {
-                self.vector.x = v.x.clamp(-1.0, 1.0);
-                self.vector.y = v.y.clamp(0.0, 1.0);
-            }
+            PropulsionCommand::SetThrust(v) =>
+                self.set_thrust(DirectionVector{x: v.x, y: v.y}),
         }
     }
 
+    pub fn set_thrust(&mut self, v: DirectionVector) {
+        if self.inhibited {
+            eprintln!("Propulsion is inhibited, ignoring command.");
+            return;
+        }
+
+        self.vector.x = v.x.clamp(-1.0, 1.0);
+        self.vector.y = v.y.clamp(0.0, 1.0);
+    }
+
+    /*
+     * The direction actually used, with any autopilot yaw in place of the
+     * pilot's.
+     */
     pub fn get_direction(&self) -> DirectionVector {
-        self.vector
+        DirectionVector {
+            x: self.yaw_override.unwrap_or(self.vector.x),
+            y: self.vector.y,
+        }
+    }
+
+    pub fn set_yaw_override(&mut self, yaw: Option<f32>) {
+        self.yaw_override = yaw.map(|x| x.clamp(-1.0, 1.0));
     }
 
     pub fn get_aft_state(&self) -> bool {
@@ -51,10 +97,41 @@ impl Propulsion {
         self.port_thruster.get_state()
     }
 
+    pub fn get_faults(&self) -> [ThrusterFault; 3] {
+        [
+            self.aft_thruster.get_fault(),
+            self.starboard_thruster.get_fault(),
+            self.port_thruster.get_fault(),
+        ]
+    }
+
+    // aft, starboard, port, NaN where there is no sensor
+    pub fn get_currents(&self) -> [f32; 3] {
+        [
+            self.aft_thruster.get_current(),
+            self.starboard_thruster.get_current(),
+            self.port_thruster.get_current(),
+        ]
+    }
+
+    // drops the pilot's demand, the thrusters follow on the next tick
+    pub fn zero_thrust(&mut self) {
+        self.vector = DirectionVector{x: 0.0, y: 0.0};
+    }
+
+    fn stop_all(&mut self) {
+        self.vector = DirectionVector{x: 0.0, y: 0.0};
+        self.yaw_override = None;
+        self.aft_thruster.stop();
+        self.starboard_thruster.stop();
+        self.port_thruster.stop();
+    }
+
     fn set_thruster_states(&mut self) {
-        let aft_en = self.vector.y > 0.3;
-        let sb_en = self.vector.x > 0.3;
-        let port_en = self.vector.x < -0.3;
+        let vector = self.get_direction();
+        let aft_en = vector.y > 0.3;
+        let sb_en = vector.x > 0.3;
+        let port_en = vector.x < -0.3;
 
         if sb_en && port_en {
             // this shouldn't happen and I should probably do something
@@ -69,6 +146,11 @@ impl Propulsion {
 
 impl Tick for Propulsion {
     fn tick(&mut self, tick_count: u32) {
+        if self.inhibited {
+            self.stop_all();
+            return;
+        }
+
         self.set_thruster_states();
 
         self.aft_thruster.tick(tick_count);
@@ -76,3 +158,49 @@ impl Tick for Propulsion {
         self.port_thruster.tick(tick_count);
     }
 }
+
+impl SubmarineComponent for Propulsion {
+    fn enable(&mut self) {
+        // re-enabling is how a stalled thruster is put back into service
+        self.aft_thruster.clear_fault();
+        self.starboard_thruster.clear_fault();
+        self.port_thruster.clear_fault();
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        self.stop_all();
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for Propulsion {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::Propulsion
+    }
+
+    fn handle(&mut self, cmd: &Command) {
+        match cmd {
+            Command::Propulsion(c) => self.handle_command(c.as_ref()),
+            Command::Thrust(v) => self.set_thrust(*v),
+            _ => {},
+        }
+    }
+
+    fn set_inhibited(&mut self, inhibited: bool) {
+        if inhibited && !self.inhibited {
+            self.stop_all();
+        }
+        self.inhibited = inhibited;
+    }
+
+    fn serialize_telemetry(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        let mut telemetry = PropulsionTelemetry::new();
+        telemetry.collect(self);
+        telemetry.serialize(buffer)
+    }
+}
@@ -1,19 +1,26 @@
 use rppal::gpio::{ Gpio, OutputPin };
 use crate::{
     traits::Tick,
-    error::PeripheralInitError,
+    error::{ InitFailure, PeripheralInitError },
+    config::hardware::propulsion::{ CurrentSenseConfig, CurrentSensorConfig },
 };
+use super::current_monitor::{ CurrentMonitor, ThrusterFault };
 
 pub struct ThrusterController {
     control_pin: OutputPin,
     target_state: bool,
+    current_monitor: Option<CurrentMonitor>,
 }
 
 impl ThrusterController {
-    pub fn new(control_pin: u8) -> Result<Self, PeripheralInitError> {
+    pub fn new(
+        control_pin: u8,
+        current_sense: Option<(&CurrentSenseConfig, &CurrentSensorConfig)>,
+    ) -> Result<Self, PeripheralInitError> {
         Ok(Self {
             control_pin: Gpio::new().map_err(|e| {
                 PeripheralInitError{
+                    reason: InitFailure::Gpio,
                     message: format!(
                         "Failed to init Gpio for pin {}: {}",
                         control_pin,
@@ -22,6 +29,7 @@ impl ThrusterController {
                 }
             })?.get(control_pin).map_err(|e| {
                 PeripheralInitError {
+                    reason: InitFailure::Gpio,
                     message: format!(
                         "Failed to get gpio pin {}: {}",
                         control_pin,
@@ -30,7 +38,10 @@ impl ThrusterController {
                 }
             })?.into_output(),
 
-            target_state: false
+            target_state: false,
+            current_monitor: current_sense
+                .map(|(config, sensor)| CurrentMonitor::new(config, sensor))
+                .transpose()?,
         })
     }
 
@@ -38,21 +49,61 @@ impl ThrusterController {
         self.target_state = en;
     }
 
+    pub fn stop(&mut self) {
+        self.target_state = false;
+        self.control_pin.set_low();
+    }
+
+    pub fn clear_fault(&mut self) {
+        if let Some(m) = self.current_monitor.as_mut() { m.clear_fault(); }
+    }
+
     pub fn get_state(&self) -> bool {
         self.control_pin.is_set_high()
     }
 
+    pub fn get_fault(&self) -> ThrusterFault {
+        self.current_monitor.as_ref().map_or(ThrusterFault::None, |m| m.get_fault())
+    }
+
+    // NaN when the thruster has no current sensor
+    pub fn get_current(&self) -> f32 {
+        self.current_monitor.as_ref().map_or(f32::NAN, |m| m.get_current())
+    }
+
     fn update(&mut self) {
-        if self.target_state {
+        // a stalled thruster stays off until the fault is cleared
+        if self.target_state && self.get_fault() != ThrusterFault::Stall {
             self.control_pin.set_high();
         } else {
             self.control_pin.set_low();
         }
     }
+
+    fn check_current(&mut self) {
+        let powered = self.control_pin.is_set_high();
+        let Some(monitor) = self.current_monitor.as_mut() else { return; };
+        let previous = monitor.get_fault();
+        let fault = monitor.sample(powered);
+
+        if fault != previous && fault != ThrusterFault::None {
+            eprintln!(
+                "Thruster on pin {} faulted: {:?} at {:.2}A",
+                self.control_pin.pin(),
+                fault,
+                monitor.get_current()
+            );
+        }
+
+        if fault == ThrusterFault::Stall {
+            self.control_pin.set_low();
+        }
+    }
 }
 
 impl Tick for ThrusterController {
     fn tick(&mut self, _tick_count: u32) {
-        self.update()
+        self.update();
+        self.check_current();
     }
 }
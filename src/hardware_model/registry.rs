@@ -0,0 +1,172 @@
+use super::ComponentKind;
+use crate::{
+    error::{ InitFailure, PeripheralInitError },
+    traits::Component,
+};
+
+/*
+ * Every configured component gets a slot, in config order. The slot index
+ * is the component's ID, used to address it in commands and telemetry. A
+ * slot stays empty when an optional component fails to initialize, so the
+ * IDs of the other components do not depend on which hardware came up.
+ */
+pub struct ComponentSlot {
+    pub id: u8,
+    pub name: String,
+    pub kind: ComponentKind,
+    component: Option<Box<dyn Component>>,
+    init_error: Option<PeripheralInitError>,
+}
+
+impl ComponentSlot {
+    pub fn component(&self) -> Option<&dyn Component> {
+        self.component.as_deref()
+    }
+
+    pub fn component_mut(&mut self) -> Option<&mut (dyn Component + 'static)> {
+        self.component.as_deref_mut()
+    }
+
+    pub fn get_init_error(&self) -> Option<&PeripheralInitError> {
+        self.init_error.as_ref()
+    }
+}
+
+pub struct ComponentRegistry {
+    slots: Vec<ComponentSlot>,
+}
+
+impl ComponentRegistry {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+        }
+    }
+
+    /*
+     * A required component that fails to initialize aborts startup. An
+     * optional one is left out and its error is kept, so the failure can
+     * be reported over telemetry.
+     */
+    pub fn register<C: Component + 'static>(
+        &mut self,
+        name: &str,
+        kind: ComponentKind,
+        required: bool,
+        init: impl FnOnce() -> Result<C, PeripheralInitError>,
+    ) -> Result<(), PeripheralInitError> {
+        if self.find(name).is_some() {
+            return Err(PeripheralInitError {
+                reason: InitFailure::Config,
+                message: format!("Duplicate component name: {}", name),
+            });
+        }
+
+        let id = u8::try_from(self.slots.len()).map_err(|_| {
+            PeripheralInitError {
+                reason: InitFailure::Config,
+                message: format!("Too many components to register {}", name),
+            }
+        })?;
+
+        let (component, init_error) = match init() {
+            Ok(c) => (Some(Box::new(c) as Box<dyn Component>), None),
+            Err(e) if required => return Err(PeripheralInitError {
+                reason: e.reason,
+                message: format!("{} (required): {}", name, e.message),
+            }),
+            // telemetry only flags the failure, the reason is logged
+            // against the module ID it is reported under
+            Err(e) => {
+                eprintln!("Leaving out module {} ({}): {}", id, name, e.message);
+                (None, Some(e))
+            }
+        };
+
+        self.slots.push(ComponentSlot {
+            id,
+            name: String::from(name),
+            kind,
+            component,
+            init_error,
+        });
+
+        Ok(())
+    }
+
+    pub fn slots(&self) -> &[ComponentSlot] {
+        &self.slots
+    }
+
+    pub fn slots_mut(&mut self) -> &mut [ComponentSlot] {
+        &mut self.slots
+    }
+
+    pub fn get_mut(&mut self, id: u8) -> Option<&mut ComponentSlot> {
+        self.slots.get_mut(id as usize)
+    }
+
+    pub fn find(&self, name: &str) -> Option<&ComponentSlot> {
+        self.slots.iter().find(|s| s.name == name)
+    }
+
+    /*
+     * Typed access to every present component of type T, for the few
+     * places that need more than the Component trait offers.
+     */
+    pub fn iter_of<T: Component + 'static>(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter()
+            .filter_map(|s| s.component())
+            .filter_map(|c| c.as_any().downcast_ref::<T>())
+    }
+
+    pub fn iter_mut_of<T: Component + 'static>(&mut self)
+        -> impl Iterator<Item = &mut T>
+    {
+        self.slots.iter_mut()
+            .filter_map(|s| s.component_mut())
+            .filter_map(|c| c.as_any_mut().downcast_mut::<T>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware_model::Battery;
+
+    fn failure(reason: InitFailure) -> Result<Battery, PeripheralInitError> {
+        Err(PeripheralInitError { reason, message: String::from("no battery") })
+    }
+
+    #[test]
+    fn keeps_the_failure_of_an_optional_component() {
+        let mut registry = ComponentRegistry::new();
+        registry.register("battery", ComponentKind::Battery, false,
+            || failure(InitFailure::Bus)).unwrap();
+
+        let slot = registry.find("battery").unwrap();
+        assert!(slot.component().is_none());
+        assert_eq!(slot.get_init_error().map(|e| e.reason), Some(InitFailure::Bus));
+    }
+
+    #[test]
+    fn fails_on_a_required_component() {
+        let mut registry = ComponentRegistry::new();
+        let result = registry.register("battery", ComponentKind::Battery, true,
+            || failure(InitFailure::Device));
+
+        assert_eq!(result.map_err(|e| e.reason).err(), Some(InitFailure::Device));
+        assert!(registry.find("battery").is_none());
+    }
+
+    #[test]
+    fn rejects_duplicate_names() {
+        let mut registry = ComponentRegistry::new();
+        registry.register("battery", ComponentKind::Battery, false,
+            || failure(InitFailure::Bus)).unwrap();
+        let result = registry.register("battery", ComponentKind::Battery, false,
+            || failure(InitFailure::Bus));
+
+        assert_eq!(result.map_err(|e| e.reason).err(), Some(InitFailure::Config));
+    }
+}
@@ -0,0 +1,97 @@
+use rppal::uart::{ Parity, Uart };
+use std::time::Duration;
+use crate::{
+    config::hardware::serial::SerialConfig,
+    error::{ InitFailure, PeripheralInitError },
+};
+
+/*
+ * Opens a serial device for non-blocking reads, so components can drain
+ * whatever has arrived once per tick.
+ */
+pub fn open(config: &SerialConfig) -> Result<Uart, PeripheralInitError> {
+    let mut uart = Uart::with_path(&config.device, config.baud, Parity::None, 8, 1)
+        .map_err(|e| PeripheralInitError {
+            reason: InitFailure::Bus,
+            message: format!("Failed to open serial device {}: {}", config.device, e),
+        })?;
+
+    uart.set_read_mode(0, Duration::ZERO).map_err(|e| PeripheralInitError {
+        reason: InitFailure::Bus,
+        message: format!("Failed to configure serial device {}: {}", config.device, e),
+    })?;
+
+    Ok(uart)
+}
+
+/*
+ * Reads everything currently buffered, passing it on in chunks.
+ */
+pub fn drain(uart: &mut Uart, mut consume: impl FnMut(&[u8])) -> Result<(), String> {
+    let mut buffer = [0u8; 64];
+
+    loop {
+        let read = uart.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 { return Ok(()); }
+
+        consume(&buffer[..read]);
+    }
+}
+
+/*
+ * A pseudo-terminal for tests, standing in for a serial device. Bytes
+ * written to it arrive on the device end, and whatever the component
+ * sends can be read back.
+ */
+#[cfg(test)]
+pub mod fake {
+    use nix::{ pty, sys::termios, unistd };
+    use std::{
+        fs::File,
+        io::{ Read, Write },
+        os::fd::{ AsRawFd, OwnedFd },
+    };
+    use crate::config::hardware::serial::SerialConfig;
+
+    pub struct FakeDevice {
+        master: File,
+        // kept open so the device end doesn't hang up between reads
+        _slave: OwnedFd,
+        pub config: SerialConfig,
+    }
+
+    impl FakeDevice {
+        pub fn new() -> Self {
+            let pty = pty::openpty(None, None).unwrap();
+
+            // raw, so line endings and binary frames pass through untouched
+            let mut attributes = termios::tcgetattr(&pty.slave).unwrap();
+            termios::cfmakeraw(&mut attributes);
+            termios::tcsetattr(&pty.slave, termios::SetArg::TCSANOW, &attributes).unwrap();
+
+            let device = unistd::ttyname(pty.slave.as_raw_fd()).unwrap();
+
+            Self {
+                master: File::from(pty.master),
+                _slave: pty.slave,
+                config: SerialConfig {
+                    device: device.to_string_lossy().into_owned(),
+                    baud: 115_200,
+                },
+            }
+        }
+
+        pub fn send(&mut self, bytes: &[u8]) {
+            self.master.write_all(bytes).unwrap();
+            self.master.flush().unwrap();
+            // let the line discipline pass it through
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+
+        pub fn receive(&mut self, length: usize) -> Vec<u8> {
+            let mut buffer = vec![0u8; length];
+            self.master.read_exact(&mut buffer).unwrap();
+            buffer
+        }
+    }
+}
@@ -0,0 +1,255 @@
+mod pca9685;
+
+use pca9685::Pca9685Driver;
+use rppal::pwm::{ Channel, Polarity, Pwm };
+use std::time::Duration;
+use crate::{
+    command::Command,
+    config::hardware::servo::{ ServoConfig, ServoDriverConfig },
+    error::{ InitFailure, PeripheralInitError },
+    hardware_model::ComponentKind,
+    telemetry::{ ServoTelemetry, TELEMETRY_PACKET_SIZE },
+    traits::{ Component, SubmarineComponent, Tick },
+};
+
+// standard 50Hz servo frame
+const SERVO_PERIOD_US: u32 = 20_000;
+
+trait ServoDriver {
+    fn set_pulse_width(&mut self, pulse_us: u32) -> Result<(), String>;
+    // stop sending pulses, leaving the servo unpowered
+    fn stop(&mut self) -> Result<(), String>;
+}
+
+struct PwmDriver {
+    pwm: Pwm,
+}
+
+impl PwmDriver {
+    fn new(channel: u8) -> Result<Self, PeripheralInitError> {
+        let channel = match channel {
+            0 => Channel::Pwm0,
+            1 => Channel::Pwm1,
+            _ => return Err(PeripheralInitError {
+                reason: InitFailure::Bus,
+                message: format!("Invalid PWM channel {}", channel),
+            }),
+        };
+
+        Ok(Self {
+            pwm: Pwm::with_period(
+                channel,
+                Duration::from_micros(SERVO_PERIOD_US as u64),
+                Duration::ZERO,
+                Polarity::Normal,
+                false
+            ).map_err(|e| PeripheralInitError {
+                reason: InitFailure::Bus,
+                message: format!("Failed to get servo pwm channel: {}", e),
+            })?,
+        })
+    }
+}
+
+impl ServoDriver for PwmDriver {
+    fn set_pulse_width(&mut self, pulse_us: u32) -> Result<(), String> {
+        self.pwm.set_pulse_width(Duration::from_micros(pulse_us as u64))
+            .and_then(|_| self.pwm.enable())
+            .map_err(|e| e.to_string())
+    }
+
+    fn stop(&mut self) -> Result<(), String> {
+        self.pwm.disable().map_err(|e| e.to_string())
+    }
+}
+
+/*
+ * A servo driving a control surface, such as the dive planes or the
+ * rudder. Angles are in degrees from center. The commanded angle is
+ * clamped to the surface's travel limits, and the angle sent to the servo
+ * moves towards it by at most max_step_deg per tick.
+ */
+pub struct Servo {
+    driver: Box<dyn ServoDriver>,
+    min_pulse_us: u32,
+    max_pulse_us: u32,
+    pulse_range_deg: f32,
+    center_trim_us: i32,
+    min_angle_deg: f32,
+    max_angle_deg: f32,
+    max_step_deg: f32,
+    commanded_angle: f32,
+    current_angle: f32,
+    pulse_us: u32,
+    enabled: bool,
+    inhibited: bool,
+}
+
+impl Servo {
+    pub fn new(config: &ServoConfig) -> Result<Self, PeripheralInitError> {
+        let finite = [config.min_angle_deg, config.max_angle_deg,
+            config.pulse_range_deg, config.max_step_deg].iter().all(|v| v.is_finite());
+
+        if !finite
+            || config.min_pulse_us >= config.max_pulse_us
+            || config.min_angle_deg > config.max_angle_deg
+            || config.pulse_range_deg <= 0.0
+            || config.max_step_deg <= 0.0 {
+
+            return Err(PeripheralInitError {
+                reason: InitFailure::Config,
+                message: format!("Invalid servo limits for {}", config.name),
+            });
+        }
+
+        let driver: Box<dyn ServoDriver> = match config.driver {
+            ServoDriverConfig::Pwm { channel } =>
+                Box::new(PwmDriver::new(channel)?),
+            ServoDriverConfig::Pca9685 { bus, address, channel } =>
+                Box::new(Pca9685Driver::new(bus, address, channel)?),
+        };
+
+        let mut servo = Self {
+            driver,
+            min_pulse_us: config.min_pulse_us,
+            max_pulse_us: config.max_pulse_us,
+            pulse_range_deg: config.pulse_range_deg,
+            center_trim_us: config.center_trim_us,
+            min_angle_deg: config.min_angle_deg,
+            max_angle_deg: config.max_angle_deg,
+            max_step_deg: config.max_step_deg,
+            commanded_angle: 0.0,
+            current_angle: 0.0,
+            pulse_us: 0,
+            enabled: true,
+            inhibited: false,
+        };
+
+        servo.commanded_angle = servo.clamp_angle(0.0);
+        servo.current_angle = servo.commanded_angle;
+        servo.update().map_err(|e| PeripheralInitError {
+            reason: InitFailure::Device,
+            message: format!("Failed to center servo {}: {}", config.name, e),
+        })?;
+
+        Ok(servo)
+    }
+
+    pub fn set_angle(&mut self, angle_deg: f32) {
+        // a NaN would stick in current_angle for good
+        if !angle_deg.is_finite() {
+            eprintln!("Ignoring servo angle {}.", angle_deg);
+            return;
+        }
+        if self.inhibited {
+            eprintln!("Servo is inhibited, ignoring command.");
+            return;
+        }
+        self.commanded_angle = self.clamp_angle(angle_deg);
+    }
+
+    pub fn get_commanded_angle(&self) -> f32 {
+        self.commanded_angle
+    }
+
+    pub fn get_current_angle(&self) -> f32 {
+        self.current_angle
+    }
+
+    pub fn get_pulse_width(&self) -> u32 {
+        self.pulse_us
+    }
+
+    fn clamp_angle(&self, angle_deg: f32) -> f32 {
+        angle_deg.clamp(self.min_angle_deg, self.max_angle_deg)
+    }
+
+    fn angle_to_pulse(&self, angle_deg: f32) -> u32 {
+        let center = (self.min_pulse_us + self.max_pulse_us) as f32 / 2.0
+            + self.center_trim_us as f32;
+        let us_per_deg = (self.max_pulse_us - self.min_pulse_us) as f32
+            / self.pulse_range_deg;
+
+        (center + angle_deg * us_per_deg).round()
+            .clamp(self.min_pulse_us as f32, self.max_pulse_us as f32) as u32
+    }
+
+    fn update(&mut self) -> Result<(), String> {
+        let step = (self.commanded_angle - self.current_angle)
+            .clamp(-self.max_step_deg, self.max_step_deg);
+        self.current_angle += step;
+
+        let pulse_us = self.angle_to_pulse(self.current_angle);
+        if pulse_us != self.pulse_us {
+            self.driver.set_pulse_width(pulse_us)?;
+            self.pulse_us = pulse_us;
+        }
+
+        Ok(())
+    }
+}
+
+impl Tick for Servo {
+    fn tick(&mut self, _tick_count: u32) {
+        if self.inhibited { return; }
+
+        if let Err(e) = self.update() {
+            eprintln!("Failed to drive servo: {}", e);
+        }
+    }
+}
+
+impl SubmarineComponent for Servo {
+    fn enable(&mut self) {
+        // force the pulse to be resent on the next tick
+        self.pulse_us = 0;
+        self.enabled = true;
+    }
+
+    fn disable(&mut self) {
+        if let Err(e) = self.driver.stop() {
+            eprintln!("Failed to stop servo: {}", e);
+        }
+        self.enabled = false;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl Component for Servo {
+    fn kind(&self) -> ComponentKind {
+        ComponentKind::Servo
+    }
+
+    fn handle(&mut self, cmd: &Command) {
+        if let Command::Servo(c) = cmd {
+            self.set_angle(c.angle_deg);
+        }
+    }
+
+    /*
+     * Freezes the surface where it is and stops the pulse, so a servo
+     * still slewing doesn't carry on to its commanded angle.
+     */
+    fn set_inhibited(&mut self, inhibited: bool) {
+        if inhibited == self.inhibited { return; }
+
+        if inhibited {
+            self.commanded_angle = self.current_angle;
+            if let Err(e) = self.driver.stop() {
+                eprintln!("Failed to stop servo: {}", e);
+            }
+        }
+        // force the pulse to be resent on the next tick
+        self.pulse_us = 0;
+        self.inhibited = inhibited;
+    }
+
+    fn serialize_telemetry(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        let mut telemetry = ServoTelemetry::new();
+        telemetry.collect(self);
+        telemetry.serialize(buffer)
+    }
+}
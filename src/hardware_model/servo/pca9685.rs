@@ -0,0 +1,90 @@
+// https://www.nxp.com/docs/en/data-sheet/PCA9685.pdf
+
+use rppal::i2c::I2c;
+use std::{ thread, time::Duration };
+use crate::error::{ InitFailure, PeripheralInitError };
+use super::{ ServoDriver, SERVO_PERIOD_US };
+
+const MODE1: u8 = 0x00;
+const LED0_ON_L: u8 = 0x06;
+const PRESCALE: u8 = 0xFE;
+
+const MODE1_SLEEP: u8 = 0x10;
+const MODE1_AUTO_INCREMENT: u8 = 0x20;
+const MODE1_RESTART: u8 = 0x80;
+const FULL_OFF: u8 = 0x10;
+
+const OSCILLATOR_HZ: u32 = 25_000_000;
+const COUNTS: u32 = 4096;
+
+pub struct Pca9685Driver {
+    i2c: I2c,
+    channel: u8,
+}
+
+impl Pca9685Driver {
+    pub fn new(bus: u8, address: u16, channel: u8)
+        -> Result<Self, PeripheralInitError>
+    {
+        if channel > 15 {
+            return Err(PeripheralInitError {
+                reason: InitFailure::Bus,
+                message: format!("Invalid PCA9685 channel {}", channel),
+            });
+        }
+
+        let mut i2c = I2c::with_bus(bus).map_err(|e| PeripheralInitError {
+            reason: InitFailure::Bus,
+            message: format!("Failed to open I2C bus {}: {}", bus, e),
+        })?;
+        i2c.set_slave_address(address).map_err(|e| PeripheralInitError {
+            reason: InitFailure::Device,
+            message: format!("Failed to address PCA9685 at {:#X}: {}", address, e),
+        })?;
+
+        let mut driver = Self { i2c, channel };
+        driver.configure().map_err(|e| PeripheralInitError {
+            reason: InitFailure::Device,
+            message: format!("Failed to configure PCA9685 at {:#X}: {}", address, e),
+        })?;
+
+        Ok(driver)
+    }
+
+    /*
+     * The prescaler can only be written while the oscillator is asleep.
+     */
+    fn configure(&mut self) -> Result<(), rppal::i2c::Error> {
+        let prescale = OSCILLATOR_HZ / (COUNTS * (1_000_000 / SERVO_PERIOD_US)) - 1;
+
+        self.i2c.write(&[MODE1, MODE1_SLEEP])?;
+        self.i2c.write(&[PRESCALE, prescale as u8])?;
+        self.i2c.write(&[MODE1, MODE1_AUTO_INCREMENT])?;
+        thread::sleep(Duration::from_micros(500));
+        self.i2c.write(&[MODE1, MODE1_AUTO_INCREMENT | MODE1_RESTART])?;
+
+        Ok(())
+    }
+
+    fn write_channel(&mut self, on: u16, off: u16) -> Result<(), String> {
+        let register = LED0_ON_L + 4 * self.channel;
+        let on = on.to_le_bytes();
+        let off = off.to_le_bytes();
+
+        self.i2c.write(&[register, on[0], on[1], off[0], off[1]])
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl ServoDriver for Pca9685Driver {
+    fn set_pulse_width(&mut self, pulse_us: u32) -> Result<(), String> {
+        let off = (pulse_us * COUNTS / SERVO_PERIOD_US).min(COUNTS - 1);
+
+        self.write_channel(0, off as u16)
+    }
+
+    fn stop(&mut self) -> Result<(), String> {
+        self.write_channel(0, (FULL_OFF as u16) << 8)
+    }
+}
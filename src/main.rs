@@ -1,5 +1,8 @@
+mod action;
+mod clock;
 mod command;
 mod config;
+mod control;
 mod definitions;
 mod error;
 mod hardware_model;
@@ -7,7 +10,7 @@ mod telemetry;
 mod traits;
 
 use traits::Tick;
-use hardware_model::Submarine;
+use hardware_model::{ Submarine, StatusCode };
 use telemetry::Telemetry;
 use std::time::Duration;
 use std::sync::{ Arc, atomic::{ AtomicBool, Ordering} };
@@ -16,7 +19,13 @@ use ctrlc;
 #[tokio::main]
 async fn main() {
     println!("Initializing system..");
-    let (mut sub, mut telemetry, sys_config) = init_system().unwrap();
+    let (mut sub, mut telemetry, sys_config) = match init_system() {
+        Ok(system) => system,
+        Err(e) => {
+            eprintln!("Initialization failed: {}", e.message);
+            std::process::exit(1);
+        }
+    };
     println!("Initialization complete.");
     run_system(&mut sub, &mut telemetry, sys_config.tick_rate).await;
     stop_system();
@@ -27,14 +36,22 @@ fn init_system() -> Result<
     error::PeripheralInitError
 > {
     println!("Loading configs.");
-    let config = config::Config::load();
+    let config = config::Config::load()
+        .map_err(|message| error::PeripheralInitError {
+            reason: error::InitFailure::Config,
+            message,
+        })?;
 
     command::start_command_listener(&config.commanding);
     println!("Instantiating telemetry.");
     let telemetry = Telemetry::new(&config.telemetry);
 
     Ok((
-        hardware_model::Submarine::new(&config.hardware)?,
+        hardware_model::Submarine::new(
+            &config.hardware,
+            &config.control,
+            config.system.tick_rate
+        )?,
         telemetry,
         config.system,
     ))
@@ -68,6 +85,8 @@ async fn run_system(
         telem.collect_system_telemetry(tick_delta, delay);
 
         command::dispatch(sub);
+        action::run_pending(sub);
+        report_status(sub, telem, tick_delta > tick_interval);
         sub.tick(tick_count);
 
         telem.collect_hw_telemetry(sub);
@@ -83,6 +102,18 @@ async fn run_system(
     }
 }
 
+fn report_status(sub: &mut Submarine, telem: &Telemetry, tick_overrun: bool) {
+    sub.set_status(
+        StatusCode::TelemetryReaderMissing,
+        !telem.is_emitter_ready()
+    );
+    sub.set_status(
+        StatusCode::CommandSocketError,
+        !command::is_listener_healthy()
+    );
+    sub.set_status(StatusCode::TickOverrun, tick_overrun);
+}
+
 fn stop_system() {
     println!("Shutting down");
 }
@@ -0,0 +1,167 @@
+use crate::hardware_model::Submarine;
+use super::TELEMETRY_PACKET_SIZE;
+
+const SERIALIZED_BUFFER_SIZE: u8 = 21;
+const AUTO_TRIM_BUFFER_SIZE: u8 = 18;
+
+const ENGAGED: u8 = 0x1;
+const MEASUREMENT_VALID: u8 = 0x2;
+
+/*
+ * State of the depth hold loop. Stays all zero when depth hold isn't
+ * configured.
+ */
+pub struct DepthHoldTelemetry {
+    pub flags: u8,
+    pub setpoint_m: f32,
+    pub depth_m: f32,
+    pub error_m: f32,
+    pub output: f32,
+    pub integral_term: f32,
+}
+
+impl DepthHoldTelemetry {
+    pub fn new() -> Self {
+        Self {
+            flags: 0x0,
+            setpoint_m: 0.0,
+            depth_m: 0.0,
+            error_m: 0.0,
+            output: 0.0,
+            integral_term: 0.0,
+        }
+    }
+}
+
+impl super::Telemeter for DepthHoldTelemetry {
+    fn collect(&mut self, sub: &Submarine) {
+        let Some(depth_hold) = sub.depth_hold() else { return };
+        self.flags = 0x0;
+
+        if depth_hold.is_engaged() { self.flags |= ENGAGED; }
+        if let Some(s) = depth_hold.get_setpoint() { self.setpoint_m = s; }
+        if let Some(d) = depth_hold.get_depth() {
+            self.depth_m = d;
+            self.flags |= MEASUREMENT_VALID;
+        }
+        self.error_m = depth_hold.get_error();
+        self.output = depth_hold.get_output();
+        self.integral_term = depth_hold.get_integral_term();
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0] = self.flags;
+        buffer[1..5].copy_from_slice(&self.setpoint_m.to_le_bytes());
+        buffer[5..9].copy_from_slice(&self.depth_m.to_le_bytes());
+        buffer[9..13].copy_from_slice(&self.error_m.to_le_bytes());
+        buffer[13..17].copy_from_slice(&self.output.to_le_bytes());
+        buffer[17..21].copy_from_slice(&self.integral_term.to_le_bytes());
+
+        SERIALIZED_BUFFER_SIZE
+    }
+}
+
+/*
+ * State of the heading hold loop, in degrees. Stays all zero when heading
+ * hold isn't configured.
+ */
+pub struct HeadingHoldTelemetry {
+    pub flags: u8,
+    pub setpoint_deg: f32,
+    pub heading_deg: f32,
+    pub error_deg: f32,
+    pub output: f32,
+    pub integral_term: f32,
+}
+
+impl HeadingHoldTelemetry {
+    pub fn new() -> Self {
+        Self {
+            flags: 0x0,
+            setpoint_deg: 0.0,
+            heading_deg: 0.0,
+            error_deg: 0.0,
+            output: 0.0,
+            integral_term: 0.0,
+        }
+    }
+}
+
+impl super::Telemeter for HeadingHoldTelemetry {
+    fn collect(&mut self, sub: &Submarine) {
+        let Some(heading_hold) = sub.heading_hold() else { return };
+        self.flags = 0x0;
+
+        if let Some(s) = heading_hold.get_setpoint() {
+            self.setpoint_deg = s;
+            self.flags |= ENGAGED;
+        }
+        if let Some(h) = heading_hold.get_heading() {
+            self.heading_deg = h;
+            self.flags |= MEASUREMENT_VALID;
+        }
+        self.error_deg = heading_hold.get_error();
+        self.output = heading_hold.get_output();
+        self.integral_term = heading_hold.get_integral_term();
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0] = self.flags;
+        buffer[1..5].copy_from_slice(&self.setpoint_deg.to_le_bytes());
+        buffer[5..9].copy_from_slice(&self.heading_deg.to_le_bytes());
+        buffer[9..13].copy_from_slice(&self.error_deg.to_le_bytes());
+        buffer[13..17].copy_from_slice(&self.output.to_le_bytes());
+        buffer[17..21].copy_from_slice(&self.integral_term.to_le_bytes());
+
+        SERIALIZED_BUFFER_SIZE
+    }
+}
+
+/*
+ * Progress of the auto-trim routine and the stored result. The position
+ * is u32::MAX and the pump time NaN when not known.
+ */
+pub struct AutoTrimTelemetry {
+    pub state: u8,
+    pub pulses: u8,
+    pub pulse_s: f32,
+    pub depth_rate_mps: f32,
+    pub trim_position: u32,
+    pub trim_pump_s: f32,
+}
+
+impl AutoTrimTelemetry {
+    pub fn new() -> Self {
+        Self {
+            state: 0x0,
+            pulses: 0,
+            pulse_s: 0.0,
+            depth_rate_mps: 0.0,
+            trim_position: u32::MAX,
+            trim_pump_s: f32::NAN,
+        }
+    }
+}
+
+impl super::Telemeter for AutoTrimTelemetry {
+    fn collect(&mut self, sub: &Submarine) {
+        let Some(trim) = sub.auto_trim() else { return };
+
+        self.state = trim.get_state() as u8;
+        self.pulses = trim.get_pulses();
+        self.pulse_s = trim.get_pulse_length();
+        self.depth_rate_mps = trim.get_depth_rate();
+
+        let calibration = trim.get_calibration();
+        self.trim_position = calibration.and_then(|c| c.position).unwrap_or(u32::MAX);
+        self.trim_pump_s = calibration.and_then(|c| c.pump_seconds).unwrap_or(f32::NAN);
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0] = self.state;
+        buffer[1] = self.pulses;
+        buffer[2..6].copy_from_slice(&self.pulse_s.to_le_bytes());
+        buffer[6..10].copy_from_slice(&self.depth_rate_mps.to_le_bytes());
+        buffer[10..14].copy_from_slice(&self.trim_position.to_le_bytes());
+        buffer[14..18].copy_from_slice(&self.trim_pump_s.to_le_bytes());
+
+        AUTO_TRIM_BUFFER_SIZE
+    }
+}
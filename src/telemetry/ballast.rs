@@ -1,11 +1,23 @@
-use crate::hardware_model::Submarine;
+use crate::hardware_model::Ballast;
 use super::TELEMETRY_PACKET_SIZE;
 
-const SERIALIZED_BUFFER_SIZE: u8 = 2;
+const SERIALIZED_BUFFER_SIZE: u8 = 8;
+
+const FULL_SWITCH_PRESENT: u8 = 0x1;
+const EMPTY_SWITCH_PRESENT: u8 = 0x2;
+const FULL_SWITCH_ACTIVE: u8 = 0x4;
+const EMPTY_SWITCH_ACTIVE: u8 = 0x8;
+
+const POSITION_VALID: u8 = 0x1;
+const HOMED: u8 = 0x2;
+const POSITION_TARGET_ACTIVE: u8 = 0x4;
 
 pub struct BallastTelemetry {
     pub current_state: u8,
     pub target_state: u8,
+    pub limit_switches: u8,
+    pub position: u32,
+    pub position_flags: u8,
 }
 
 impl BallastTelemetry {
@@ -13,20 +25,38 @@ impl BallastTelemetry {
         Self {
             current_state: 0x0,
             target_state: 0x0,
+            limit_switches: 0x0,
+            position: 0,
+            position_flags: 0x0,
         }
     }
-}
-
-impl super::Telemeter for BallastTelemetry {
-    fn collect(&mut self, sub: &Submarine) {
-        let ballast = &sub.ballast;
 
+    pub fn collect(&mut self, ballast: &Ballast) {
         self.current_state = ballast.get_current_state() as u8;
         self.target_state = ballast.get_target_state() as u8;
+
+        self.limit_switches = 0x0;
+        if ballast.has_full_switch() { self.limit_switches |= FULL_SWITCH_PRESENT; }
+        if ballast.has_empty_switch() { self.limit_switches |= EMPTY_SWITCH_PRESENT; }
+        if ballast.is_full() { self.limit_switches |= FULL_SWITCH_ACTIVE; }
+        if ballast.is_empty() { self.limit_switches |= EMPTY_SWITCH_ACTIVE; }
+
+        self.position_flags = 0x0;
+        self.position = match ballast.get_position() {
+            Some(p) => { self.position_flags |= POSITION_VALID; p },
+            None => 0,
+        };
+        if ballast.is_homed() { self.position_flags |= HOMED; }
+        if ballast.get_position_target().is_some() {
+            self.position_flags |= POSITION_TARGET_ACTIVE;
+        }
     }
-    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+    pub fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
         buffer[0] = self.current_state;
         buffer[1] = self.target_state;
+        buffer[2] = self.limit_switches;
+        buffer[3..7].copy_from_slice(&self.position.to_le_bytes());
+        buffer[7] = self.position_flags;
 
         SERIALIZED_BUFFER_SIZE
     }
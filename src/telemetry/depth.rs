@@ -0,0 +1,44 @@
+use crate::hardware_model::DepthSensor;
+use super::TELEMETRY_PACKET_SIZE;
+
+const SERIALIZED_BUFFER_SIZE: u8 = 13;
+
+const DEPTH_VALID: u8 = 0x1;
+// pressure and temperature are good, depth also needs the surface pressure
+const READING_VALID: u8 = 0x2;
+
+pub struct DepthTelemetry {
+    pub depth_m: f32,
+    pub pressure_mbar: f32,
+    pub water_temperature_c: f32,
+    pub flags: u8,
+}
+
+impl DepthTelemetry {
+    pub fn new() -> Self {
+        Self {
+            depth_m: 0.0,
+            pressure_mbar: 0.0,
+            water_temperature_c: 0.0,
+            flags: 0x0,
+        }
+    }
+
+    pub fn collect(&mut self, sensor: &DepthSensor) {
+        self.depth_m = sensor.get_depth().unwrap_or(0.0);
+        self.pressure_mbar = sensor.get_pressure();
+        self.water_temperature_c = sensor.get_temperature();
+        self.flags = 0x0;
+        if sensor.get_depth().is_some() { self.flags |= DEPTH_VALID; }
+        if sensor.is_last_read_valid() { self.flags |= READING_VALID; }
+    }
+
+    pub fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0..4].copy_from_slice(&self.depth_m.to_le_bytes());
+        buffer[4..8].copy_from_slice(&self.pressure_mbar.to_le_bytes());
+        buffer[8..12].copy_from_slice(&self.water_temperature_c.to_le_bytes());
+        buffer[12] = self.flags;
+
+        SERIALIZED_BUFFER_SIZE
+    }
+}
@@ -0,0 +1,48 @@
+use crate::hardware_model::EchoSounder;
+use super::TELEMETRY_PACKET_SIZE;
+
+const SERIALIZED_BUFFER_SIZE: u8 = 10;
+
+const RANGE_VALID: u8 = 0x1;
+const LOW_ALTITUDE: u8 = 0x2;
+
+pub struct EchoSounderTelemetry {
+    pub range_m: f32,
+    pub confidence: u8,
+    pub range_valid: bool,
+    pub low_altitude: bool,
+    pub checksum_failures: u32,
+}
+
+impl EchoSounderTelemetry {
+    pub fn new() -> Self {
+        Self {
+            range_m: 0.0,
+            confidence: 0,
+            range_valid: false,
+            low_altitude: false,
+            checksum_failures: 0,
+        }
+    }
+
+    pub fn collect(&mut self, echo_sounder: &EchoSounder) {
+        self.range_m = echo_sounder.get_range_m().unwrap_or(0.0);
+        self.confidence = echo_sounder.get_confidence();
+        self.range_valid = !echo_sounder.is_stale();
+        self.low_altitude = echo_sounder.is_low_altitude();
+        self.checksum_failures = echo_sounder.get_checksum_failures();
+    }
+
+    pub fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0..4].copy_from_slice(&self.range_m.to_le_bytes());
+        buffer[4] = self.confidence;
+
+        let mut flags = 0x0;
+        if self.range_valid { flags |= RANGE_VALID; }
+        if self.low_altitude { flags |= LOW_ALTITUDE; }
+        buffer[5] = flags;
+        buffer[6..10].copy_from_slice(&self.checksum_failures.to_le_bytes());
+
+        SERIALIZED_BUFFER_SIZE
+    }
+}
@@ -1,35 +1,79 @@
-use crate::hardware_model::Submarine;
+use crate::hardware_model::{ Dht11, Ds18b20 };
 use super::TELEMETRY_PACKET_SIZE;
 
-const SERIALIZED_BUFFER_SIZE: u8 = 3;
+const SERIALIZED_BUFFER_SIZE: u8 = 20;
 
+// which readings the packet carries
+const HULL_READINGS: u8 = 0x1;
+const WATER_READINGS: u8 = 0x2;
+
+/*
+ * Conditions inside the hull from a DHT11, or outside it from a DS18B20
+ * probe. Each sensor sends its own environment packet (0x0), numbered by
+ * instance across both kinds in registration order.
+ */
 pub struct EnvironmentTelemetry {
+    pub sources: u8,
     pub internal_temperature_c: u8,
     pub internal_humidity_percent: u8,
     pub is_stale: bool,
+    pub dew_point_c: f32,
+    pub humidity_rate: f32,
+    pub condensation_alarms: u8,
+    pub water_temperature_c: f32,
+    pub water_temperature_valid: bool,
+    pub water_crc_failures: u16,
 }
 
 impl EnvironmentTelemetry {
     pub fn new() -> Self {
         Self {
+            sources: 0x0,
             internal_temperature_c: 0,
             internal_humidity_percent: 0,
             is_stale: true,
+            dew_point_c: 0.0,
+            humidity_rate: 0.0,
+            condensation_alarms: 0x0,
+            water_temperature_c: 0.0,
+            water_temperature_valid: false,
+            water_crc_failures: 0,
         }
     }
-}
 
-impl super::Telemeter for EnvironmentTelemetry {
-    fn collect(&mut self, sub: &Submarine) {
-        self.internal_temperature_c = sub.dht11.get_temperature();
-        self.internal_humidity_percent = sub.dht11.get_humidity();
-        self.is_stale = sub.dht11.is_last_read_valid();
+    pub fn collect(&mut self, dht11: &Dht11) {
+        self.internal_temperature_c = dht11.get_temperature();
+        self.internal_humidity_percent = dht11.get_humidity();
+        self.is_stale = dht11.is_last_read_valid();
+
+        let condensation = dht11.get_condensation();
+        self.dew_point_c = condensation.get_dew_point();
+        self.humidity_rate = condensation.get_humidity_rate();
+        self.condensation_alarms = condensation.get_alarms();
+        self.sources |= HULL_READINGS;
+    }
+
+    pub fn collect_water(&mut self, probe: &Ds18b20) {
+        self.water_temperature_c = probe.get_temperature().unwrap_or(0.0);
+        self.water_temperature_valid = probe.is_reading_valid();
+        self.water_crc_failures = probe.get_crc_failures();
+        self.sources |= WATER_READINGS;
     }
-    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+
+    pub fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
         buffer[0] = self.internal_temperature_c;
         buffer[1] = self.internal_humidity_percent;
         buffer[2] = self.is_stale as u8;
 
+        buffer[3..7].copy_from_slice(&self.dew_point_c.to_le_bytes());
+        buffer[7..11].copy_from_slice(&self.humidity_rate.to_le_bytes());
+        buffer[11] = self.condensation_alarms;
+
+        buffer[12] = self.sources;
+        buffer[13..17].copy_from_slice(&self.water_temperature_c.to_le_bytes());
+        buffer[17] = self.water_temperature_valid as u8;
+        buffer[18..20].copy_from_slice(&self.water_crc_failures.to_le_bytes());
+
         SERIALIZED_BUFFER_SIZE
     }
 }
@@ -0,0 +1,66 @@
+use crate::hardware_model::Gps;
+use super::TELEMETRY_PACKET_SIZE;
+
+const SERIALIZED_BUFFER_SIZE: u8 = 27;
+
+const FIX_KNOWN: u8 = 0x1;
+const FIX_CURRENT: u8 = 0x2;
+const FIX_TIME_KNOWN: u8 = 0x4;
+
+/*
+ * The position is the last known fix, which is kept while submerged.
+ */
+pub struct GpsTelemetry {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub fix_time: u32,
+    pub quality: u8,
+    pub satellites: u8,
+    pub flags: u8,
+    pub parse_failures: u32,
+}
+
+impl GpsTelemetry {
+    pub fn new() -> Self {
+        Self {
+            latitude: 0.0,
+            longitude: 0.0,
+            fix_time: 0,
+            quality: 0,
+            satellites: 0,
+            flags: 0x0,
+            parse_failures: 0,
+        }
+    }
+
+    pub fn collect(&mut self, gps: &Gps) {
+        self.quality = gps.get_quality();
+        self.satellites = gps.get_satellites();
+        self.parse_failures = gps.get_parse_failures();
+        self.flags = 0x0;
+
+        if let Some(fix) = gps.get_last_fix() {
+            self.latitude = fix.position.latitude;
+            self.longitude = fix.position.longitude;
+            self.flags |= FIX_KNOWN;
+
+            if let Some(t) = fix.unix_time {
+                self.fix_time = t;
+                self.flags |= FIX_TIME_KNOWN;
+            }
+        }
+        if gps.has_fix() { self.flags |= FIX_CURRENT; }
+    }
+
+    pub fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0..8].copy_from_slice(&self.latitude.to_le_bytes());
+        buffer[8..16].copy_from_slice(&self.longitude.to_le_bytes());
+        buffer[16..20].copy_from_slice(&self.fix_time.to_le_bytes());
+        buffer[20] = self.quality;
+        buffer[21] = self.satellites;
+        buffer[22] = self.flags;
+        buffer[23..27].copy_from_slice(&self.parse_failures.to_le_bytes());
+
+        SERIALIZED_BUFFER_SIZE
+    }
+}
@@ -0,0 +1,260 @@
+use crate::{
+    hardware_model::Submarine,
+    config::telemetry::HostTelemetryConfig,
+};
+use super::TELEMETRY_PACKET_SIZE;
+use std::{
+    fs,
+    path::PathBuf,
+    time::{ Duration, Instant },
+};
+
+const SERIALIZED_BUFFER_SIZE: u8 = 25;
+
+// bits of the validity byte, set when the matching field could be read
+const SOC_TEMPERATURE_VALID: u8 = 0x01;
+const THROTTLE_FLAGS_VALID: u8 = 0x02;
+const CPU_LOAD_VALID: u8 = 0x04;
+const RSS_VALID: u8 = 0x08;
+const FREE_DISK_VALID: u8 = 0x10;
+const UPTIME_VALID: u8 = 0x20;
+
+/*
+ * Where host health readings come from. The sysfs implementation is used
+ * on the vehicle; anything else can stand in for it.
+ */
+pub trait HostSource {
+    fn soc_temperature_c(&self) -> Option<f32>;
+    // raw bits as reported by the firmware's get_throttled
+    fn throttle_flags(&self) -> Option<u32>;
+    // one minute load average
+    fn cpu_load(&self) -> Option<f32>;
+    fn process_rss_kb(&self) -> Option<u32>;
+    fn free_disk_mb(&self) -> Option<u32>;
+    fn uptime_s(&self) -> Option<u32>;
+}
+
+/*
+ * Reads host health from /sys and /proc. The roots are configurable so the
+ * source can be pointed at a fake tree.
+ */
+pub struct SysfsHostSource {
+    sys_root: PathBuf,
+    proc_root: PathBuf,
+    log_partition: PathBuf,
+}
+
+impl SysfsHostSource {
+    pub fn new(config: &HostTelemetryConfig) -> Self {
+        Self {
+            sys_root: PathBuf::from(&config.sys_root),
+            proc_root: PathBuf::from(&config.proc_root),
+            log_partition: PathBuf::from(&config.log_partition),
+        }
+    }
+
+    fn read_sys(&self, path: &str) -> Option<String> {
+        fs::read_to_string(self.sys_root.join(path)).ok()
+    }
+
+    fn read_proc(&self, path: &str) -> Option<String> {
+        fs::read_to_string(self.proc_root.join(path)).ok()
+    }
+}
+
+impl HostSource for SysfsHostSource {
+    fn soc_temperature_c(&self) -> Option<f32> {
+        let millidegrees: i32 = self.read_sys("class/thermal/thermal_zone0/temp")?
+            .trim().parse().ok()?;
+
+        Some(millidegrees as f32 / 1000.0)
+    }
+
+    fn throttle_flags(&self) -> Option<u32> {
+        let raw = self.read_sys("devices/platform/soc/soc:firmware/get_throttled")?;
+        let raw = raw.trim();
+
+        u32::from_str_radix(raw.trim_start_matches("0x"), 16).ok()
+    }
+
+    fn cpu_load(&self) -> Option<f32> {
+        self.read_proc("loadavg")?
+            .split_whitespace().next()?
+            .parse().ok()
+    }
+
+    fn process_rss_kb(&self) -> Option<u32> {
+        self.read_proc("self/status")?
+            .lines()
+            .find(|l| l.starts_with("VmRSS:"))?
+            .split_whitespace().nth(1)?
+            .parse().ok()
+    }
+
+    fn free_disk_mb(&self) -> Option<u32> {
+        let stat = nix::sys::statvfs::statvfs(&self.log_partition).ok()?;
+        let free_bytes =
+            stat.blocks_available() as u64 * stat.fragment_size() as u64;
+
+        Some((free_bytes / (1024 * 1024)) as u32)
+    }
+
+    fn uptime_s(&self) -> Option<u32> {
+        let uptime: f64 = self.read_proc("uptime")?
+            .split_whitespace().next()?
+            .parse().ok()?;
+
+        Some(uptime as u32)
+    }
+}
+
+pub struct HostTelemetry {
+    source: Box<dyn HostSource>,
+    sample_interval: Duration,
+    last_sample: Option<Instant>,
+    pub soc_temperature_c: Option<f32>,
+    pub throttle_flags: Option<u32>,
+    pub cpu_load: Option<f32>,
+    pub process_rss_kb: Option<u32>,
+    pub free_disk_mb: Option<u32>,
+    pub uptime_s: Option<u32>,
+}
+
+impl HostTelemetry {
+    pub fn new(source: Box<dyn HostSource>, sample_interval: Duration) -> Self {
+        Self {
+            source,
+            sample_interval,
+            last_sample: None,
+            soc_temperature_c: None,
+            throttle_flags: None,
+            cpu_load: None,
+            process_rss_kb: None,
+            free_disk_mb: None,
+            uptime_s: None,
+        }
+    }
+
+    fn sample(&mut self) {
+        self.soc_temperature_c = self.source.soc_temperature_c();
+        self.throttle_flags = self.source.throttle_flags();
+        self.cpu_load = self.source.cpu_load();
+        self.process_rss_kb = self.source.process_rss_kb();
+        self.free_disk_mb = self.source.free_disk_mb();
+        self.uptime_s = self.source.uptime_s();
+    }
+}
+
+impl super::Telemeter for HostTelemetry {
+    fn collect(&mut self, _sub: &Submarine) {
+        let due = self.last_sample
+            .map_or(true, |t| t.elapsed() >= self.sample_interval);
+
+        if due {
+            self.sample();
+            self.last_sample = Some(Instant::now());
+        }
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        let mut valid = 0x0;
+
+        if let Some(t) = self.soc_temperature_c {
+            buffer[0..4].copy_from_slice(&t.to_le_bytes());
+            valid |= SOC_TEMPERATURE_VALID;
+        }
+        if let Some(f) = self.throttle_flags {
+            buffer[4..8].copy_from_slice(&f.to_le_bytes());
+            valid |= THROTTLE_FLAGS_VALID;
+        }
+        if let Some(l) = self.cpu_load {
+            buffer[8..12].copy_from_slice(&l.to_le_bytes());
+            valid |= CPU_LOAD_VALID;
+        }
+        if let Some(r) = self.process_rss_kb {
+            buffer[12..16].copy_from_slice(&r.to_le_bytes());
+            valid |= RSS_VALID;
+        }
+        if let Some(d) = self.free_disk_mb {
+            buffer[16..20].copy_from_slice(&d.to_le_bytes());
+            valid |= FREE_DISK_VALID;
+        }
+        if let Some(u) = self.uptime_s {
+            buffer[20..24].copy_from_slice(&u.to_le_bytes());
+            valid |= UPTIME_VALID;
+        }
+        buffer[24] = valid;
+
+        SERIALIZED_BUFFER_SIZE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Telemeter;
+    use std::path::Path;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn source(root: &Path) -> SysfsHostSource {
+        SysfsHostSource::new(&HostTelemetryConfig {
+            sample_interval_ms: 0,
+            sys_root: root.join("sys").to_string_lossy().into_owned(),
+            proc_root: root.join("proc").to_string_lossy().into_owned(),
+            log_partition: root.to_string_lossy().into_owned(),
+        })
+    }
+
+    #[test]
+    fn reads_a_fake_tree() {
+        let root = tempfile::tempdir().unwrap();
+        write(root.path(), "sys/class/thermal/thermal_zone0/temp", "48312\n");
+        write(root.path(), "sys/devices/platform/soc/soc:firmware/get_throttled", "0x50005\n");
+        write(root.path(), "proc/loadavg", "0.42 0.30 0.25 1/123 4567\n");
+        write(root.path(), "proc/self/status", "Name:\tsystem_controller\nVmRSS:\t  5120 kB\n");
+        write(root.path(), "proc/uptime", "3600.55 7000.10\n");
+
+        let source = source(root.path());
+        assert_eq!(source.soc_temperature_c(), Some(48.312));
+        assert_eq!(source.throttle_flags(), Some(0x50005));
+        assert_eq!(source.cpu_load(), Some(0.42));
+        assert_eq!(source.process_rss_kb(), Some(5120));
+        assert_eq!(source.uptime_s(), Some(3600));
+        // the tempdir is on a real partition
+        assert!(source.free_disk_mb().is_some());
+    }
+
+    #[test]
+    fn missing_or_garbled_files_are_none() {
+        let root = tempfile::tempdir().unwrap();
+        write(root.path(), "sys/class/thermal/thermal_zone0/temp", "hot\n");
+        write(root.path(), "proc/self/status", "Name:\tsystem_controller\n");
+
+        let source = source(root.path());
+        assert_eq!(source.soc_temperature_c(), None);
+        assert_eq!(source.throttle_flags(), None);
+        assert_eq!(source.cpu_load(), None);
+        assert_eq!(source.process_rss_kb(), None);
+        assert_eq!(source.uptime_s(), None);
+    }
+
+    #[test]
+    fn serializes_only_valid_fields() {
+        let root = tempfile::tempdir().unwrap();
+        write(root.path(), "proc/loadavg", "1.50 1.00 0.50 1/123 4567\n");
+        write(root.path(), "proc/uptime", "12.0 24.0\n");
+
+        let mut telemetry = HostTelemetry::new(Box::new(source(root.path())), Duration::ZERO);
+        telemetry.sample();
+
+        let mut buffer = [0u8; TELEMETRY_PACKET_SIZE];
+        assert_eq!(telemetry.serialize(&mut buffer), SERIALIZED_BUFFER_SIZE);
+        assert_eq!(buffer[8..12], 1.5f32.to_le_bytes());
+        assert_eq!(buffer[20..24], 12u32.to_le_bytes());
+        assert_eq!(buffer[24] & !FREE_DISK_VALID, CPU_LOAD_VALID | UPTIME_VALID);
+    }
+}
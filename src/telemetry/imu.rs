@@ -0,0 +1,42 @@
+use crate::hardware_model::Imu;
+use super::TELEMETRY_PACKET_SIZE;
+
+const SERIALIZED_BUFFER_SIZE: u8 = 14;
+
+pub struct ImuTelemetry {
+    pub heading_deg: f32,
+    pub roll_deg: f32,
+    pub pitch_deg: f32,
+    pub calibration: u8,
+    pub is_valid: bool,
+}
+
+impl ImuTelemetry {
+    pub fn new() -> Self {
+        Self {
+            heading_deg: 0.0,
+            roll_deg: 0.0,
+            pitch_deg: 0.0,
+            calibration: 0,
+            is_valid: false,
+        }
+    }
+
+    pub fn collect(&mut self, imu: &Imu) {
+        self.heading_deg = imu.get_heading().unwrap_or(0.0);
+        self.roll_deg = imu.get_roll();
+        self.pitch_deg = imu.get_pitch();
+        self.calibration = imu.get_calibration();
+        self.is_valid = imu.is_last_read_valid();
+    }
+
+    pub fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0..4].copy_from_slice(&self.heading_deg.to_le_bytes());
+        buffer[4..8].copy_from_slice(&self.roll_deg.to_le_bytes());
+        buffer[8..12].copy_from_slice(&self.pitch_deg.to_le_bytes());
+        buffer[12] = self.calibration;
+        buffer[13] = self.is_valid as u8;
+
+        SERIALIZED_BUFFER_SIZE
+    }
+}
@@ -0,0 +1,31 @@
+use crate::hardware_model::Input;
+use super::TELEMETRY_PACKET_SIZE;
+
+const SERIALIZED_BUFFER_SIZE: u8 = 5;
+
+pub struct InputTelemetry {
+    pub active: bool,
+    // total debounced edges, wrapping
+    pub edge_count: u32,
+}
+
+impl InputTelemetry {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            edge_count: 0,
+        }
+    }
+
+    pub fn collect(&mut self, input: &Input) {
+        self.active = input.is_active();
+        self.edge_count = input.get_edge_count();
+    }
+
+    pub fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0] = self.active as u8;
+        buffer[1..5].copy_from_slice(&self.edge_count.to_le_bytes());
+
+        SERIALIZED_BUFFER_SIZE
+    }
+}
@@ -1,17 +1,48 @@
+mod autopilot;
 mod ballast;
+mod depth;
+mod echo_sounder;
 mod environment;
+mod gps;
+mod host;
+mod imu;
+mod input;
+mod modules;
+mod navigation;
 mod propulsion;
+mod safety;
+mod servo;
 mod system;
-
-use ballast::BallastTelemetry;
-use environment::EnvironmentTelemetry;
-use propulsion::PropulsionTelemetry;
+mod vehicle;
+
+pub use ballast::BallastTelemetry;
+pub use depth::DepthTelemetry;
+pub use echo_sounder::EchoSounderTelemetry;
+pub use environment::EnvironmentTelemetry;
+pub use gps::GpsTelemetry;
+pub use imu::ImuTelemetry;
+pub use input::InputTelemetry;
+pub use propulsion::PropulsionTelemetry;
+pub use servo::ServoTelemetry;
+use autopilot::{ AutoTrimTelemetry, DepthHoldTelemetry, HeadingHoldTelemetry };
+use host::{ HostTelemetry, SysfsHostSource };
+use modules::{ ModuleFailureTelemetry, ModuleTelemetry };
+use navigation::NavigationTelemetry;
+use safety::SafetyTelemetry;
 use system::SystemTelemetry;
+use vehicle::{
+    EnvelopeTelemetry,
+    LinkTelemetry,
+    MissionTelemetry,
+    ModeTelemetry,
+    RulesTelemetry,
+};
 use crate::{
-    hardware_model::Submarine,
+    hardware_model::{ ComponentKind, Submarine },
     config::telemetry::TelemetryConfig,
 };
 use std::{
+    collections::HashMap,
     fs::{ remove_file, File },
     io::Write,
     time::Duration,
@@ -21,15 +52,67 @@ use std::{
 use nix::unistd;
 use tempfile::tempdir;
 
-const TELEMETRY_PACKET_SIZE: usize = 32;
+pub const TELEMETRY_PACKET_SIZE: usize = 32;
 const ID_BYTE_OFFSET: usize = 1;
 const TICK_BYTE_OFFSET: usize = 1;
 
 const ENVIRONMENT_PACKET_ID: u8 = 0x0;
 const BALLAST_PACKET_ID: u8 = 0x1;
 const PROPULSION_PACKET_ID: u8 = 0x2;
+const MODULE_PACKET_ID: u8 = 0x3;
+const SERVO_PACKET_ID: u8 = 0x4;
+const ECHO_SOUNDER_PACKET_ID: u8 = 0x5;
+const GPS_PACKET_ID: u8 = 0x6;
+const INPUT_PACKET_ID: u8 = 0x8;
+const DEPTH_PACKET_ID: u8 = 0x9;
+const IMU_PACKET_ID: u8 = 0xA;
+const VEHICLE_PACKET_ID: u8 = 0xB;
+const AUTOPILOT_PACKET_ID: u8 = 0xC;
+const SAFETY_PACKET_ID: u8 = 0xD;
+const HOST_PACKET_ID: u8 = 0xE;
 const SYSTEM_PACKET_ID: u8 = 0xF;
 
+// component packets carry the instance number in the high nibble of the ID
+const INSTANCE_SHIFT: u8 = 4;
+const MAX_INSTANCES: u8 = 0x10;
+
+// submarine-wide packets of the same type are told apart the same way
+const DEPTH_HOLD_INSTANCE: u8 = 0x0;
+const HEADING_HOLD_INSTANCE: u8 = 0x1;
+const AUTO_TRIM_INSTANCE: u8 = 0x2;
+
+// module packet instances
+const MODULE_STATUS_INSTANCE: u8 = 0x0;
+const MODULE_FAILURE_INSTANCE: u8 = 0x1;
+
+// vehicle packet instances
+const MODE_INSTANCE: u8 = 0x0;
+const LINK_INSTANCE: u8 = 0x1;
+const MISSION_INSTANCE: u8 = 0x2;
+const ENVELOPE_INSTANCE: u8 = 0x3;
+const NAVIGATION_INSTANCE: u8 = 0x4;
+const RULES_INSTANCE: u8 = 0x5;
+
+/*
+ * Packet IDs of the components that emit telemetry. Add new component
+ * packets here.
+ */
+fn component_packet_id(kind: ComponentKind) -> Option<u8> {
+    match kind {
+        ComponentKind::Dht11 => Some(ENVIRONMENT_PACKET_ID),
+        ComponentKind::Ballast => Some(BALLAST_PACKET_ID),
+        ComponentKind::Propulsion => Some(PROPULSION_PACKET_ID),
+        ComponentKind::Servo => Some(SERVO_PACKET_ID),
+        ComponentKind::EchoSounder => Some(ECHO_SOUNDER_PACKET_ID),
+        ComponentKind::Gps => Some(GPS_PACKET_ID),
+        ComponentKind::Ds18b20 => Some(ENVIRONMENT_PACKET_ID),
+        ComponentKind::Input => Some(INPUT_PACKET_ID),
+        ComponentKind::DepthSensor => Some(DEPTH_PACKET_ID),
+        ComponentKind::Imu => Some(IMU_PACKET_ID),
+        _ => None,
+    }
+}
+
 struct TelemetryPacket {
     payload: Box<dyn Telemeter>,
     id: u8,
@@ -46,8 +129,15 @@ impl TelemetryPacket {
     }
 }
 
+struct ComponentPacket {
+    buffer: [u8; TELEMETRY_PACKET_SIZE],
+    size: u8,
+    id: u8,
+}
+
 pub struct Telemetry {
     hw_packet_list: Vec<TelemetryPacket>,
+    component_packets: Vec<ComponentPacket>,
     system: (SystemTelemetry, u8, bool),
     emit_thread_handle: thread::JoinHandle<()>,
     emit_channel: mpsc::Sender<[u8; TELEMETRY_PACKET_SIZE]>,
@@ -64,15 +154,39 @@ impl Telemetry {
             Telemetry::create_transmit_thread(&config.socket, &emitter_ready);
 
         Self {
-            // add new telemetry packets here
+            // add new submarine-wide telemetry packets here
             hw_packet_list: vec![
-                TelemetryPacket::new(Box::new(EnvironmentTelemetry::new()),
-                    ENVIRONMENT_PACKET_ID),
-                TelemetryPacket::new(Box::new(BallastTelemetry::new()),
-                    BALLAST_PACKET_ID),
-                TelemetryPacket::new(Box::new(PropulsionTelemetry::new()),
-                    PROPULSION_PACKET_ID),
+                TelemetryPacket::new(Box::new(ModuleTelemetry::new()),
+                    MODULE_PACKET_ID | MODULE_STATUS_INSTANCE << INSTANCE_SHIFT),
+                TelemetryPacket::new(Box::new(ModuleFailureTelemetry::new()),
+                    MODULE_PACKET_ID | MODULE_FAILURE_INSTANCE << INSTANCE_SHIFT),
+                TelemetryPacket::new(Box::new(HostTelemetry::new(
+                        Box::new(SysfsHostSource::new(&config.host)),
+                        Duration::from_millis(config.host.sample_interval_ms as u64),
+                    )),
+                    HOST_PACKET_ID),
+                TelemetryPacket::new(Box::new(SafetyTelemetry::new()),
+                    SAFETY_PACKET_ID),
+                TelemetryPacket::new(Box::new(ModeTelemetry::new()),
+                    VEHICLE_PACKET_ID | MODE_INSTANCE << INSTANCE_SHIFT),
+                TelemetryPacket::new(Box::new(LinkTelemetry::new()),
+                    VEHICLE_PACKET_ID | LINK_INSTANCE << INSTANCE_SHIFT),
+                TelemetryPacket::new(Box::new(MissionTelemetry::new()),
+                    VEHICLE_PACKET_ID | MISSION_INSTANCE << INSTANCE_SHIFT),
+                TelemetryPacket::new(Box::new(EnvelopeTelemetry::new()),
+                    VEHICLE_PACKET_ID | ENVELOPE_INSTANCE << INSTANCE_SHIFT),
+                TelemetryPacket::new(Box::new(NavigationTelemetry::new()),
+                    VEHICLE_PACKET_ID | NAVIGATION_INSTANCE << INSTANCE_SHIFT),
+                TelemetryPacket::new(Box::new(RulesTelemetry::new()),
+                    VEHICLE_PACKET_ID | RULES_INSTANCE << INSTANCE_SHIFT),
+                TelemetryPacket::new(Box::new(DepthHoldTelemetry::new()),
+                    AUTOPILOT_PACKET_ID | DEPTH_HOLD_INSTANCE << INSTANCE_SHIFT),
+                TelemetryPacket::new(Box::new(HeadingHoldTelemetry::new()),
+                    AUTOPILOT_PACKET_ID | HEADING_HOLD_INSTANCE << INSTANCE_SHIFT),
+                TelemetryPacket::new(Box::new(AutoTrimTelemetry::new()),
+                    AUTOPILOT_PACKET_ID | AUTO_TRIM_INSTANCE << INSTANCE_SHIFT),
             ],
+            component_packets: Vec::new(),
             system: (SystemTelemetry::new(), SYSTEM_PACKET_ID, true),
 
             emit_thread_handle: transmit_handle,
@@ -88,6 +202,10 @@ impl Telemetry {
         self.tick_count = tick_count;
     }
 
+    pub fn is_emitter_ready(&self) -> bool {
+        self.emitter_ready.load(Ordering::SeqCst)
+    }
+
     pub fn collect_hw_telemetry(&mut self, sub: &Submarine) {
         //self.hw_packet_list[0].enabled = false;
         //self.hw_packet_list[1].enabled = false;
@@ -99,6 +217,39 @@ impl Telemetry {
                 packet.payload.collect(sub);
             }
         }
+
+        self.collect_component_telemetry(sub);
+    }
+
+    /*
+     * Components sending the same packet are numbered in registration
+     * order, counting components that are absent or disabled so that an
+     * instance keeps its number.
+     */
+    fn collect_component_telemetry(&mut self, sub: &Submarine) {
+        let mut instances: HashMap<u8, u8> = HashMap::new();
+        self.component_packets.clear();
+
+        for slot in sub.components().slots() {
+            let Some(base_id) = component_packet_id(slot.kind) else { continue };
+            let instance = instances.entry(base_id).or_insert(0);
+            let id = base_id | (*instance << INSTANCE_SHIFT);
+            *instance = (*instance + 1) % MAX_INSTANCES;
+
+            match slot.component() {
+                Some(c) if c.is_enabled() => {
+                    let mut buffer = [0; TELEMETRY_PACKET_SIZE];
+                    let size = c.serialize_telemetry(&mut buffer);
+
+                    self.component_packets.push(ComponentPacket {
+                        buffer,
+                        size,
+                        id,
+                    });
+                },
+                _ => {},
+            }
+        }
     }
 
     pub fn collect_system_telemetry(
@@ -157,6 +308,17 @@ impl Telemetry {
             }
         }
 
+        for packet in self.component_packets.iter_mut() {
+            if let Err(_) = Telemetry::apply_tick_count(&mut packet.buffer, packet.size, self.tick_count) {
+                eprintln!("Not enough room in {:#X} buffer for tick count.", packet.id);
+            };
+            if let Err(_) = Telemetry::apply_packet_id(&mut packet.buffer, packet.size, packet.id) {
+                eprintln!("Not enough room in {:#X} buffer for packet ID.", packet.id);
+                continue;
+            }
+            self.emit_channel.send(packet.buffer.clone())?;
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,81 @@
+use crate::hardware_model::Submarine;
+use super::TELEMETRY_PACKET_SIZE;
+
+// what fits in front of the tick count
+const MAX_MODULES: usize = 26;
+
+const MODULE_PRESENT: u8 = 0x1;
+const MODULE_INIT_FAILED: u8 = 0x2;
+const MODULE_ENABLED: u8 = 0x4;
+
+/*
+ * One status byte per registered component, indexed by component ID. The
+ * low nibble is a set of MODULE_* flags, the high nibble is the component
+ * kind.
+ */
+pub struct ModuleTelemetry {
+    pub module_status: Vec<u8>,
+}
+
+impl ModuleTelemetry {
+    pub fn new() -> Self {
+        Self {
+            module_status: Vec::new(),
+        }
+    }
+}
+
+impl super::Telemeter for ModuleTelemetry {
+    fn collect(&mut self, sub: &Submarine) {
+        self.module_status.clear();
+
+        for slot in sub.components().slots().iter().take(MAX_MODULES) {
+            let flags = match slot.component() {
+                Some(c) if c.is_enabled() => MODULE_PRESENT | MODULE_ENABLED,
+                Some(_) => MODULE_PRESENT,
+                None if slot.get_init_error().is_some() => MODULE_INIT_FAILED,
+                None => 0x0,
+            };
+
+            self.module_status.push((slot.kind as u8) << 4 | flags);
+        }
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[..self.module_status.len()]
+            .copy_from_slice(&self.module_status);
+
+        self.module_status.len() as u8
+    }
+}
+
+/*
+ * Sent next to the status bytes as a second module packet instance: one
+ * byte per registered component, indexed by component ID, holding the
+ * InitFailure code of a component that was left out, or 0x0.
+ */
+pub struct ModuleFailureTelemetry {
+    pub reasons: Vec<u8>,
+}
+
+impl ModuleFailureTelemetry {
+    pub fn new() -> Self {
+        Self {
+            reasons: Vec::new(),
+        }
+    }
+}
+
+impl super::Telemeter for ModuleFailureTelemetry {
+    fn collect(&mut self, sub: &Submarine) {
+        self.reasons.clear();
+
+        for slot in sub.components().slots().iter().take(MAX_MODULES) {
+            self.reasons.push(slot.get_init_error().map_or(0x0, |e| e.reason as u8));
+        }
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[..self.reasons.len()].copy_from_slice(&self.reasons);
+
+        self.reasons.len() as u8
+    }
+}
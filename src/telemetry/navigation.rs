@@ -0,0 +1,59 @@
+use crate::hardware_model::Submarine;
+use super::TELEMETRY_PACKET_SIZE;
+
+const SERIALIZED_BUFFER_SIZE: u8 = 21;
+
+const ESTIMATING: u8 = 0x1;
+const IMU_HEADING: u8 = 0x2;
+const GPS_FIX: u8 = 0x4;
+
+/*
+ * The dead reckoned position, in meters from the last GPS fix, which the
+ * GPS packet carries. Stays all zero when not configured.
+ */
+pub struct NavigationTelemetry {
+    pub north_m: f32,
+    pub east_m: f32,
+    pub error_m: f32,
+    pub heading_deg: f32,
+    pub speed_mps: f32,
+    pub flags: u8,
+}
+
+impl NavigationTelemetry {
+    pub fn new() -> Self {
+        Self {
+            north_m: 0.0,
+            east_m: 0.0,
+            error_m: 0.0,
+            heading_deg: 0.0,
+            speed_mps: 0.0,
+            flags: 0x0,
+        }
+    }
+}
+
+impl super::Telemeter for NavigationTelemetry {
+    fn collect(&mut self, sub: &Submarine) {
+        let Some(d) = sub.dead_reckoning() else { return };
+
+        self.north_m = d.get_north();
+        self.east_m = d.get_east();
+        self.error_m = d.get_error();
+        self.heading_deg = d.get_heading();
+        self.speed_mps = d.get_speed();
+        self.flags = ESTIMATING;
+        if d.has_imu_heading() { self.flags |= IMU_HEADING; }
+        if d.has_gps_fix() { self.flags |= GPS_FIX; }
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0..4].copy_from_slice(&self.north_m.to_le_bytes());
+        buffer[4..8].copy_from_slice(&self.east_m.to_le_bytes());
+        buffer[8..12].copy_from_slice(&self.error_m.to_le_bytes());
+        buffer[12..16].copy_from_slice(&self.heading_deg.to_le_bytes());
+        buffer[16..20].copy_from_slice(&self.speed_mps.to_le_bytes());
+        buffer[20] = self.flags;
+
+        SERIALIZED_BUFFER_SIZE
+    }
+}
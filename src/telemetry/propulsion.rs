@@ -1,14 +1,17 @@
-use crate::hardware_model::Submarine;
+use crate::hardware_model::{ Propulsion, ThrusterFault };
 use super::TELEMETRY_PACKET_SIZE;
 use crate::definitions::DirectionVector;
 
-const SERIALIZED_BUFFER_SIZE: u8 = 9;
+const SERIALIZED_BUFFER_SIZE: u8 = 24;
 
 pub struct PropulsionTelemetry {
     pub vector: DirectionVector,
     pub aft_en: bool,
     pub sb_en: bool,
     pub port_en: bool,
+    // aft, starboard, port
+    pub faults: [ThrusterFault; 3],
+    pub currents: [f32; 3],
 }
 
 impl PropulsionTelemetry {
@@ -18,20 +21,20 @@ impl PropulsionTelemetry {
             aft_en: false,
             sb_en: false,
             port_en: false,
+            faults: [ThrusterFault::None; 3],
+            currents: [f32::NAN; 3],
         }
     }
-}
 
-impl super::Telemeter for PropulsionTelemetry {
-    fn collect(&mut self, sub: &Submarine) {
-        let propulsion = &sub.propulsion;
-        
+    pub fn collect(&mut self, propulsion: &Propulsion) {
         self.vector = propulsion.get_direction();
         self.aft_en = propulsion.get_aft_state();
         self.sb_en = propulsion.get_sb_state();
         self.port_en = propulsion.get_port_state();
+        self.faults = propulsion.get_faults();
+        self.currents = propulsion.get_currents();
     }
-    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+    pub fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
         let x_buf = self.vector.x.to_le_bytes();
         let y_buf = self.vector.y.to_le_bytes();
 
@@ -49,6 +52,14 @@ impl super::Telemeter for PropulsionTelemetry {
         buffer[8] = buffer[8] << self.sb_en as u8;
         buffer[8] = buffer[8] << self.port_en as u8;
 
+        for (i, fault) in self.faults.iter().enumerate() {
+            buffer[9 + i] = *fault as u8;
+        }
+        for (i, current) in self.currents.iter().enumerate() {
+            let start = 12 + i * 4;
+            buffer[start..start + 4].copy_from_slice(&current.to_le_bytes());
+        }
+
         SERIALIZED_BUFFER_SIZE
     }
 }
@@ -0,0 +1,61 @@
+use crate::hardware_model::Submarine;
+use super::TELEMETRY_PACKET_SIZE;
+
+const SERIALIZED_BUFFER_SIZE: u8 = 6;
+
+const KILL_SWITCH_PRESENT: u8 = 0x1;
+const ARMED: u8 = 0x2;
+const ACTUATORS_INHIBITED: u8 = 0x4;
+const EMERGENCY_STOP_LATCHED: u8 = 0x8;
+
+// reported as the E-stop source while it isn't latched
+const NO_SOURCE: u8 = 0xFF;
+
+pub struct SafetyTelemetry {
+    pub flags: u8,
+    pub arm_changes: u16,
+    // a CommandSource
+    pub emergency_stop_source: u8,
+    pub emergency_stops: u16,
+}
+
+impl SafetyTelemetry {
+    pub fn new() -> Self {
+        Self {
+            flags: 0x0,
+            arm_changes: 0,
+            emergency_stop_source: NO_SOURCE,
+            emergency_stops: 0,
+        }
+    }
+}
+
+impl super::Telemeter for SafetyTelemetry {
+    fn collect(&mut self, sub: &Submarine) {
+        self.flags = 0x0;
+
+        if let Some(kill_switch) = sub.kill_switch() {
+            self.flags |= KILL_SWITCH_PRESENT;
+            self.arm_changes = kill_switch.get_arm_changes();
+        }
+        if sub.is_armed() { self.flags |= ARMED; }
+        if sub.is_actuation_inhibited() { self.flags |= ACTUATORS_INHIBITED; }
+
+        self.emergency_stop_source = match sub.get_emergency_stop() {
+            Some(source) => {
+                self.flags |= EMERGENCY_STOP_LATCHED;
+                source as u8
+            },
+            None => NO_SOURCE,
+        };
+        self.emergency_stops = sub.get_emergency_stops();
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0] = self.flags;
+        buffer[1..3].copy_from_slice(&self.arm_changes.to_le_bytes());
+        buffer[3] = self.emergency_stop_source;
+        buffer[4..6].copy_from_slice(&self.emergency_stops.to_le_bytes());
+
+        SERIALIZED_BUFFER_SIZE
+    }
+}
@@ -0,0 +1,33 @@
+use crate::hardware_model::Servo;
+use super::TELEMETRY_PACKET_SIZE;
+
+const SERIALIZED_BUFFER_SIZE: u8 = 10;
+
+pub struct ServoTelemetry {
+    pub commanded_angle: f32,
+    pub current_angle: f32,
+    pub pulse_us: u16,
+}
+
+impl ServoTelemetry {
+    pub fn new() -> Self {
+        Self {
+            commanded_angle: 0.0,
+            current_angle: 0.0,
+            pulse_us: 0,
+        }
+    }
+
+    pub fn collect(&mut self, servo: &Servo) {
+        self.commanded_angle = servo.get_commanded_angle();
+        self.current_angle = servo.get_current_angle();
+        self.pulse_us = servo.get_pulse_width() as u16;
+    }
+    pub fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0..4].copy_from_slice(&self.commanded_angle.to_le_bytes());
+        buffer[4..8].copy_from_slice(&self.current_angle.to_le_bytes());
+        buffer[8..10].copy_from_slice(&self.pulse_us.to_le_bytes());
+
+        SERIALIZED_BUFFER_SIZE
+    }
+}
@@ -1,14 +1,17 @@
 use std::time::Duration;
 use std::fmt::Display;
+use crate::clock;
 use super::TELEMETRY_PACKET_SIZE;
 
-const SERIALIZED_BUFFER_SIZE: u8 = 12;
+const SERIALIZED_BUFFER_SIZE: u8 = 16;
 
 #[derive(Debug)]
 pub struct SystemTelemetry {
     tick_delta: Duration,
     tick_idle_time: Duration,
     total_tick_time: Duration,
+    // unix seconds from the wall clock, 0 until it has been set
+    wall_clock_s: u32,
 }
 
 impl SystemTelemetry {
@@ -17,6 +20,7 @@ impl SystemTelemetry {
             tick_delta: Duration::ZERO,
             tick_idle_time: Duration::ZERO,
             total_tick_time: Duration::ZERO,
+            wall_clock_s: 0,
         }
     }
 
@@ -24,6 +28,7 @@ impl SystemTelemetry {
         self.tick_delta = delta;
         self.tick_idle_time = delay;
         self.total_tick_time = delay.saturating_add(delta);
+        self.wall_clock_s = clock::wall_clock().map_or(0, |t| t.as_secs() as u32);
     } 
 
     pub fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) ->  u8 {
@@ -46,6 +51,8 @@ impl SystemTelemetry {
         buffer[10] = total[2];
         buffer[11] = total[3];
 
+        buffer[12..16].copy_from_slice(&self.wall_clock_s.to_le_bytes());
+
         SERIALIZED_BUFFER_SIZE
     }
 }
@@ -0,0 +1,218 @@
+use crate::{
+    control::{ Limit, LimitStatus },
+    hardware_model::Submarine,
+};
+use super::TELEMETRY_PACKET_SIZE;
+
+const MODE_BUFFER_SIZE: u8 = 8;
+const LINK_BUFFER_SIZE: u8 = 7;
+const MISSION_BUFFER_SIZE: u8 = 12;
+const ENVELOPE_BUFFER_SIZE: u8 = 20;
+const RULES_BUFFER_SIZE: u8 = 9;
+
+// reported as the last fired rule before any has fired
+const NO_RULE: u8 = 0xFF;
+
+// reported as the mission index when none has been started
+const NO_MISSION: u8 = 0xFF;
+
+// reported as the link state when the link is not monitored
+const LINK_UNMONITORED: u8 = 0xFF;
+
+pub struct ModeTelemetry {
+    pub mode: u8,
+    pub previous_mode: u8,
+    pub ticks_in_mode: u32,
+    pub rejected_commands: u16,
+}
+
+impl ModeTelemetry {
+    pub fn new() -> Self {
+        Self {
+            mode: 0x0,
+            previous_mode: 0x0,
+            ticks_in_mode: 0,
+            rejected_commands: 0,
+        }
+    }
+}
+
+impl super::Telemeter for ModeTelemetry {
+    fn collect(&mut self, sub: &Submarine) {
+        let mode = sub.mode();
+
+        self.mode = mode.get_mode() as u8;
+        self.previous_mode = mode.get_previous_mode() as u8;
+        self.ticks_in_mode = mode.get_ticks_in_mode();
+        self.rejected_commands = mode.get_rejected_commands();
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0] = self.mode;
+        buffer[1] = self.previous_mode;
+        buffer[2..6].copy_from_slice(&self.ticks_in_mode.to_le_bytes());
+        buffer[6..8].copy_from_slice(&self.rejected_commands.to_le_bytes());
+
+        MODE_BUFFER_SIZE
+    }
+}
+
+pub struct LinkTelemetry {
+    pub state: u8,
+    // u32::MAX until the first heartbeat
+    pub ms_since_heartbeat: u32,
+    pub losses: u16,
+}
+
+impl LinkTelemetry {
+    pub fn new() -> Self {
+        Self {
+            state: LINK_UNMONITORED,
+            ms_since_heartbeat: u32::MAX,
+            losses: 0,
+        }
+    }
+}
+
+impl super::Telemeter for LinkTelemetry {
+    fn collect(&mut self, sub: &Submarine) {
+        let Some(link) = sub.link() else { return };
+
+        self.state = link.get_state() as u8;
+        self.ms_since_heartbeat = link.get_time_since_heartbeat()
+            .map_or(u32::MAX, |d| d.as_millis().min(u32::MAX as u128) as u32);
+        self.losses = link.get_losses();
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0] = self.state;
+        buffer[1..5].copy_from_slice(&self.ms_since_heartbeat.to_le_bytes());
+        buffer[5..7].copy_from_slice(&self.losses.to_le_bytes());
+
+        LINK_BUFFER_SIZE
+    }
+}
+
+pub struct MissionTelemetry {
+    pub state: u8,
+    pub mission: u8,
+    pub step: u8,
+    pub step_count: u8,
+    pub step_elapsed_s: f32,
+    // NaN for steps without a fixed length
+    pub step_duration_s: f32,
+}
+
+impl MissionTelemetry {
+    pub fn new() -> Self {
+        Self {
+            state: 0x0,
+            mission: NO_MISSION,
+            step: 0,
+            step_count: 0,
+            step_elapsed_s: 0.0,
+            step_duration_s: f32::NAN,
+        }
+    }
+}
+
+impl super::Telemeter for MissionTelemetry {
+    fn collect(&mut self, sub: &Submarine) {
+        let missions = sub.missions();
+
+        self.state = missions.get_state() as u8;
+        self.mission = missions.get_mission().map_or(NO_MISSION, |m| m as u8);
+        self.step = missions.get_step().min(u8::MAX as usize) as u8;
+        self.step_count = missions.get_step_count().min(u8::MAX as usize) as u8;
+        self.step_elapsed_s = missions.get_step_elapsed();
+        self.step_duration_s = missions.get_step_duration().unwrap_or(f32::NAN);
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0] = self.state;
+        buffer[1] = self.mission;
+        buffer[2] = self.step;
+        buffer[3] = self.step_count;
+        buffer[4..8].copy_from_slice(&self.step_elapsed_s.to_le_bytes());
+        buffer[8..12].copy_from_slice(&self.step_duration_s.to_le_bytes());
+
+        MISSION_BUFFER_SIZE
+    }
+}
+
+pub struct EnvelopeTelemetry {
+    // depth, dive time, thruster time and battery, as LimitStatus
+    pub status: [u8; 4],
+    pub dive_s: f32,
+    pub thruster_on_s: f32,
+    // NaN without a battery reading
+    pub battery_v: f32,
+    pub battery_a: f32,
+}
+
+impl EnvelopeTelemetry {
+    pub fn new() -> Self {
+        Self {
+            status: [LimitStatus::Ok as u8; 4],
+            dive_s: 0.0,
+            thruster_on_s: 0.0,
+            battery_v: f32::NAN,
+            battery_a: f32::NAN,
+        }
+    }
+}
+
+impl super::Telemeter for EnvelopeTelemetry {
+    fn collect(&mut self, sub: &Submarine) {
+        let envelope = sub.envelope();
+
+        for limit in [Limit::Depth, Limit::DiveTime, Limit::ThrusterTime, Limit::Battery] {
+            self.status[limit as usize] = envelope.get_status(limit) as u8;
+        }
+        self.dive_s = envelope.get_dive_time();
+        self.thruster_on_s = envelope.get_thruster_time();
+        self.battery_v = envelope.get_battery_voltage().unwrap_or(f32::NAN);
+        self.battery_a = envelope.get_battery_current().unwrap_or(f32::NAN);
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0..4].copy_from_slice(&self.status);
+        buffer[4..8].copy_from_slice(&self.dive_s.to_le_bytes());
+        buffer[8..12].copy_from_slice(&self.thruster_on_s.to_le_bytes());
+        buffer[12..16].copy_from_slice(&self.battery_v.to_le_bytes());
+        buffer[16..20].copy_from_slice(&self.battery_a.to_le_bytes());
+
+        ENVELOPE_BUFFER_SIZE
+    }
+}
+
+pub struct RulesTelemetry {
+    // one bit per rule, in config order
+    pub active: u32,
+    pub firings: u32,
+    pub last_fired: u8,
+}
+
+impl RulesTelemetry {
+    pub fn new() -> Self {
+        Self {
+            active: 0,
+            firings: 0,
+            last_fired: NO_RULE,
+        }
+    }
+}
+
+impl super::Telemeter for RulesTelemetry {
+    fn collect(&mut self, sub: &Submarine) {
+        let rules = sub.rules();
+
+        self.active = rules.get_active();
+        self.firings = rules.get_firings();
+        self.last_fired = rules.get_last_fired()
+            .map_or(NO_RULE, |r| r.min(NO_RULE as usize - 1) as u8);
+    }
+    fn serialize(&self, buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        buffer[0..4].copy_from_slice(&self.active.to_le_bytes());
+        buffer[4..8].copy_from_slice(&self.firings.to_le_bytes());
+        buffer[8] = self.last_fired;
+
+        RULES_BUFFER_SIZE
+    }
+}
@@ -1,9 +1,59 @@
+use std::any::Any;
+use crate::{
+    command::Command,
+    hardware_model::ComponentKind,
+    telemetry::TELEMETRY_PACKET_SIZE,
+};
+
 pub trait SubmarineComponent {
     fn enable(&mut self);
     fn disable(&mut self);
+    fn is_enabled(&self) -> bool;
 }
 
 pub trait Tick {
     fn tick(&mut self, tick_count: u32);
 }
 
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/*
+ * Everything the submarine needs from a hardware component that lives in
+ * the component registry.
+ */
+pub trait Component: SubmarineComponent + Tick + AsAny {
+    fn kind(&self) -> ComponentKind;
+
+    /*
+     * Commands are routed to every component of the command's kind. A
+     * component ignores command variants that are not meant for it.
+     */
+    fn handle(&mut self, _cmd: &Command) {}
+
+    /*
+     * Called every tick. While inhibited, actuators must keep their
+     * outputs de-energized and ignore commands.
+     */
+    fn set_inhibited(&mut self, _inhibited: bool) {}
+
+    /*
+     * Returns the number of bytes written. Only called for components
+     * whose kind has a telemetry packet ID.
+     */
+    fn serialize_telemetry(&self, _buffer: &mut [u8; TELEMETRY_PACKET_SIZE]) -> u8 {
+        0
+    }
+}